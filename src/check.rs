@@ -0,0 +1,416 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::history::calculate_change;
+use crate::fields::get_field_value;
+use crate::types::PackageReport;
+use crate::version::{is_version_field, VersionChange, VersionChangeKind};
+
+/// Exit codes of a `--check` run. Anything from 2 up is an error.
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_TRIGGERED: i32 = 1;
+pub const EXIT_ERROR: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Any,
+    Increase,
+    Decrease,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Any difference between the stored and the current value.
+    Changed,
+    /// Numeric (or date, in days) change of at least `amount`.
+    Threshold {
+        direction: Direction,
+        amount: f64,
+        percent: bool,
+    },
+    /// Boolean field flipped to the given value.
+    Became(bool),
+    /// Version bump of at least the given level (major, minor or patch).
+    VersionBump(VersionChangeKind),
+    /// Version moved in a specific way (prerelease or downgrade).
+    VersionMove(VersionChangeKind),
+}
+
+/// A parsed `--check` expression: `FIELD[:CONDITION]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckExpr {
+    pub raw: String,
+    pub field: String,
+    pub condition: Condition,
+}
+
+/// The outcome of one `--check` expression for one package.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CheckResult {
+    pub package: String,
+    pub check: String,
+    pub field: String,
+    pub triggered: bool,
+    pub magnitude: u64,
+    pub baseline_recorded_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_change: Option<VersionChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CheckExpr {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let (field, condition) = match expr.split_once(':') {
+            Some((field, condition)) => (field.trim(), parse_condition(condition.trim())
+                .with_context(|| format!("Invalid check expression: {}", expr))?),
+            None => (expr.trim(), Condition::Changed),
+        };
+
+        if field.is_empty() {
+            anyhow::bail!("Invalid check expression: {} (missing field name)", expr);
+        }
+
+        Ok(Self {
+            raw: expr.to_string(),
+            field: field.to_string(),
+            condition,
+        })
+    }
+
+    /// `published` lists all versions of the package, for version classification.
+    pub fn evaluate(
+        &self,
+        baseline: Option<(&PackageReport, DateTime<Utc>)>,
+        current: &PackageReport,
+        published: &[String],
+    ) -> Result<CheckResult> {
+        // A dist-tag can appear or disappear between runs, which is a change like any other
+        let new_value = match get_field_value(current, &self.field) {
+            Some(value) => value,
+            None if self.field.starts_with("dist_tags.") => Value::Null,
+            None => anyhow::bail!("Unknown field for check: {}", self.field),
+        };
+
+        let Some((old, recorded_at)) = baseline else {
+            return Ok(self.result(&current.name, false, 0, None));
+        };
+        if old.dist_tags.is_none() && is_dist_tag_field(&self.field) {
+            return Ok(self.result(&current.name, false, 0, None));
+        }
+        let old_value = get_field_value(old, &self.field).unwrap_or(Value::Null);
+        let magnitude = calculate_change(old, current, &self.field)
+            .unwrap_or(u64::from(old_value != new_value));
+
+        let is_version_check = matches!(self.condition, Condition::VersionBump(_) | Condition::VersionMove(_));
+        // Only classified when both sides are versions; a missing side still counts as changed
+        let version_change = match (old_value.as_str(), new_value.as_str()) {
+            (Some(old_version), Some(new_version)) if is_version_check || is_version_field(&self.field) => {
+                VersionChange::between(old_version, new_version, published)
+            }
+            _ if is_version_check && !old_value.is_null() && !new_value.is_null() => {
+                anyhow::bail!("Field '{}' is not a version", self.field);
+            }
+            _ => None,
+        };
+
+        let triggered = match &self.condition {
+            Condition::Changed => old_value != new_value,
+            Condition::Threshold { direction, amount, percent } => {
+                let (Some(old_number), Some(new_number)) = (numeric_value(&old_value), numeric_value(&new_value)) else {
+                    anyhow::bail!("Field '{}' is not numeric and can't be checked against a threshold", self.field);
+                };
+                let change = if *percent {
+                    percent_change(old_number, new_number)
+                } else {
+                    new_number - old_number
+                };
+                match direction {
+                    Direction::Any => change.abs() >= *amount,
+                    Direction::Increase => change >= *amount,
+                    Direction::Decrease => -change >= *amount,
+                }
+            }
+            Condition::Became(target) => {
+                let (Some(old_bool), Some(new_bool)) = (old_value.as_bool(), new_value.as_bool()) else {
+                    anyhow::bail!("Field '{}' is not a boolean", self.field);
+                };
+                old_bool != new_bool && new_bool == *target
+            }
+            Condition::VersionBump(level) => version_change.as_ref()
+                .and_then(|change| change.kind.level())
+                .is_some_and(|change_level| Some(change_level) >= level.level()),
+            Condition::VersionMove(kind) => version_change.as_ref()
+                .is_some_and(|change| change.kind == *kind),
+        };
+
+        let mut result = self.result(&current.name, triggered, magnitude, Some(recorded_at));
+        result.version_change = version_change;
+        Ok(result)
+    }
+
+    fn result(
+        &self,
+        package: &str,
+        triggered: bool,
+        magnitude: u64,
+        baseline_recorded_at: Option<DateTime<Utc>>,
+    ) -> CheckResult {
+        CheckResult {
+            package: package.to_string(),
+            check: self.raw.clone(),
+            field: self.field.clone(),
+            triggered,
+            magnitude,
+            baseline_recorded_at,
+            version_change: None,
+            error: None,
+        }
+    }
+}
+
+/// Evaluates every expression; a failing check is reported in its result
+/// instead of aborting the others.
+pub fn evaluate_all(
+    exprs: &[String],
+    baseline: Option<(&PackageReport, DateTime<Utc>)>,
+    current: &PackageReport,
+    published: &[String],
+) -> Vec<CheckResult> {
+    exprs.iter()
+        .map(|expr| {
+            CheckExpr::parse(expr)
+                .and_then(|check| check.evaluate(baseline, current, published))
+                .unwrap_or_else(|e| failed(expr, &current.name, baseline, format!("{:#}", e)))
+        })
+        .collect()
+}
+
+/// Every check as an error, for when the current report can't be trusted to
+/// compare against, e.g. because its download counts are stand-in zeros.
+pub fn fail_all(
+    exprs: &[String],
+    baseline: Option<(&PackageReport, DateTime<Utc>)>,
+    current: &PackageReport,
+    message: &str,
+) -> Vec<CheckResult> {
+    exprs.iter()
+        .map(|expr| failed(expr, &current.name, baseline, message.to_string()))
+        .collect()
+}
+
+fn failed(
+    expr: &str,
+    package: &str,
+    baseline: Option<(&PackageReport, DateTime<Utc>)>,
+    error: String,
+) -> CheckResult {
+    CheckResult {
+        package: package.to_string(),
+        check: expr.to_string(),
+        field: expr.split(':').next().unwrap_or_default().to_string(),
+        triggered: false,
+        magnitude: 0,
+        baseline_recorded_at: baseline.map(|(_, recorded_at)| recorded_at),
+        version_change: None,
+        error: Some(error),
+    }
+}
+
+/// Combined exit code, by precedence: any error, then any triggered check, then ok.
+pub fn exit_code(results: &[CheckResult]) -> i32 {
+    if results.iter().any(|r| r.error.is_some()) {
+        EXIT_ERROR
+    } else if results.iter().any(|r| r.triggered) {
+        EXIT_TRIGGERED
+    } else {
+        EXIT_OK
+    }
+}
+
+fn parse_condition(condition: &str) -> Result<Condition> {
+    match condition {
+        "" | "changed" => return Ok(Condition::Changed),
+        "became-true" => return Ok(Condition::Became(true)),
+        "became-false" => return Ok(Condition::Became(false)),
+        "major" => return Ok(Condition::VersionBump(VersionChangeKind::Major)),
+        "minor" => return Ok(Condition::VersionBump(VersionChangeKind::Minor)),
+        "patch" => return Ok(Condition::VersionBump(VersionChangeKind::Patch)),
+        "prerelease" => return Ok(Condition::VersionMove(VersionChangeKind::Prerelease)),
+        "downgrade" => return Ok(Condition::VersionMove(VersionChangeKind::Downgrade)),
+        _ => {}
+    }
+
+    let (direction, rest) = if let Some(rest) = condition.strip_prefix('+') {
+        (Direction::Increase, rest)
+    } else if let Some(rest) = condition.strip_prefix('-') {
+        (Direction::Decrease, rest)
+    } else {
+        (Direction::Any, condition)
+    };
+
+    let (amount, percent) = match rest.strip_suffix('%') {
+        Some(amount) => (amount, true),
+        None => (rest, false),
+    };
+
+    let amount: f64 = amount.parse()
+        .ok()
+        .filter(|a: &f64| a.is_finite() && *a >= 0.0)
+        .with_context(|| format!(
+            "Unknown condition '{}'. Use N, +N, -N, N%, +N%, -N%, changed, became-true, became-false, major, minor, patch, prerelease or downgrade",
+            condition
+        ))?;
+
+    Ok(Condition::Threshold { direction, amount, percent })
+}
+
+// Dates compare in days so thresholds like `last_publish_date:30` read naturally
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => DateTime::parse_from_rfc3339(s)
+            .map(|d| d.timestamp())
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S UTC").map(|d| d.and_utc().timestamp()))
+            .ok()
+            .map(|seconds| seconds as f64 / 86_400.0),
+        _ => None,
+    }
+}
+
+fn is_dist_tag_field(field: &str) -> bool {
+    field == "dist_tags" || field.starts_with("dist_tags.")
+}
+
+fn percent_change(old: f64, new: f64) -> f64 {
+    if old == 0.0 {
+        if new == 0.0 { 0.0 } else { f64::INFINITY.copysign(new) }
+    } else {
+        (new - old) / old.abs() * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DistTag;
+    use std::collections::BTreeMap;
+
+    fn report(weekly: u64) -> PackageReport {
+        PackageReport { name: "demo".to_string(), downloads_last_week: weekly, ..Default::default() }
+    }
+
+    fn evaluate(expr: &str, old: &PackageReport, new: &PackageReport) -> Result<CheckResult> {
+        CheckExpr::parse(expr)?.evaluate(Some((old, Utc::now())), new, &[])
+    }
+
+    #[test]
+    fn parses_conditions() {
+        let cases = [
+            ("latest_version", Condition::Changed),
+            ("latest_version:changed", Condition::Changed),
+            ("latest_version:major", Condition::VersionBump(VersionChangeKind::Major)),
+            ("latest_version:downgrade", Condition::VersionMove(VersionChangeKind::Downgrade)),
+            ("package_alive:became-false", Condition::Became(false)),
+            ("downloads_last_week:500", Condition::Threshold { direction: Direction::Any, amount: 500.0, percent: false }),
+            ("downloads_last_week:+10%", Condition::Threshold { direction: Direction::Increase, amount: 10.0, percent: true }),
+            ("downloads_last_week: -20% ", Condition::Threshold { direction: Direction::Decrease, amount: 20.0, percent: true }),
+        ];
+        for (expr, condition) in cases {
+            let check = CheckExpr::parse(expr).unwrap();
+            assert_eq!(check.condition, condition, "{}", expr);
+            assert_eq!(check.raw, expr);
+        }
+        assert_eq!(CheckExpr::parse(" name :changed").unwrap().field, "name");
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expr in ["", ":5", "downloads_last_week:abc", "downloads_last_week:--5", "downloads_last_week:inf", "x:5%%"] {
+            assert!(CheckExpr::parse(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn percent_change_handles_zero_baseline() {
+        assert_eq!(percent_change(100.0, 80.0), -20.0);
+        assert_eq!(percent_change(-50.0, -25.0), 50.0);
+        assert_eq!(percent_change(0.0, 0.0), 0.0);
+        assert_eq!(percent_change(0.0, 5.0), f64::INFINITY);
+        assert_eq!(percent_change(0.0, -5.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn evaluates_thresholds() {
+        let (old, new) = (report(1000), report(790));
+        assert!(evaluate("downloads_last_week:-20%", &old, &new).unwrap().triggered);
+        assert!(!evaluate("downloads_last_week:+20%", &old, &new).unwrap().triggered);
+        assert!(evaluate("downloads_last_week:210", &old, &new).unwrap().triggered);
+        assert!(!evaluate("downloads_last_week:211", &old, &new).unwrap().triggered);
+        assert!(evaluate("downloads_last_week:+100%", &report(0), &report(1)).unwrap().triggered);
+        assert!(evaluate("name:5", &old, &new).is_err());
+    }
+
+    #[test]
+    fn nothing_triggers_without_baseline() {
+        let check = CheckExpr::parse("downloads_last_week:1").unwrap();
+        let result = check.evaluate(None, &report(5), &[]).unwrap();
+        assert!(!result.triggered);
+        assert_eq!(result.baseline_recorded_at, None);
+    }
+
+    #[test]
+    fn errors_take_precedence_in_exit_code() {
+        let results = evaluate_all(
+            &["downloads_last_week:1".to_string(), "name:5".to_string()],
+            Some((&report(0), Utc::now())),
+            &report(10),
+            &[],
+        );
+        assert!(results[0].triggered);
+        assert!(results[1].error.is_some());
+        assert_eq!(exit_code(&results), EXIT_ERROR);
+        assert_eq!(exit_code(&results[..1]), EXIT_TRIGGERED);
+        assert_eq!(exit_code(&[]), EXIT_OK);
+    }
+
+    #[test]
+    fn fail_all_reports_every_check_as_error() {
+        let checks = ["downloads_last_week:-20%".to_string(), "latest_version".to_string()];
+        let results = fail_all(&checks, None, &report(0), "download statistics unavailable");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].field, "downloads_last_week");
+        assert!(results.iter().all(|r| !r.triggered && r.error.as_deref() == Some("download statistics unavailable")));
+        assert_eq!(exit_code(&results), EXIT_ERROR);
+    }
+
+    #[test]
+    fn missing_dist_tag_is_a_change() {
+        let mut old = report(0);
+        old.dist_tags = Some(BTreeMap::from([("latest".to_string(), DistTag::default())]));
+        let mut new = old.clone();
+        new.dist_tags.as_mut().unwrap().insert("next".to_string(), DistTag { version: "2.0.0-rc.1".to_string(), published: None });
+
+        for (before, after) in [(&old, &new), (&new, &old)] {
+            assert!(evaluate("dist_tags.next.version", before, after).unwrap().triggered);
+            assert!(!evaluate("dist_tags.next.version:major", before, after).unwrap().triggered);
+        }
+        assert!(evaluate("no_such_field", &old, &new).is_err());
+    }
+
+    #[test]
+    fn history_without_dist_tags_is_no_baseline() {
+        let old = report(0);
+        let mut new = report(0);
+        new.dist_tags = Some(BTreeMap::from([("next".to_string(), DistTag::default())]));
+
+        for expr in ["dist_tags", "dist_tags.next.version"] {
+            let result = evaluate(expr, &old, &new).unwrap();
+            assert!(!result.triggered, "{}", expr);
+            assert_eq!(result.baseline_recorded_at, None);
+        }
+    }
+}
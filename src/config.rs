@@ -1,112 +1,859 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use chrono::{NaiveDate, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
-#[derive(Parser, Debug, Clone)]
+use crate::check::CheckExpr;
+use crate::fields::validate_path;
+use crate::history::{RetentionPolicy, PACKAGE_PLACEHOLDER};
+use crate::output::compile_template;
+use crate::types::Thresholds;
+
+const GLOBAL_CONFIG: &str = "npm-activity-check/config.toml";
+const PROJECT_CONFIG: &str = ".npm-activity-check.toml";
+/// Upper bound for day counts, a century of history.
+const MAX_DAYS: i64 = 36_500;
+
+#[derive(Parser, Debug, Clone, Serialize)]
 #[command(name = "npm-activity-check")]
 #[command(about = "Check if NPM packages are actively maintained")]
 #[command(version)]
+#[command(subcommand_negates_reqs = true, disable_help_subcommand = true)]
+#[command(override_usage = "npm-activity-check [OPTIONS] [PACKAGES]...\n       npm-activity-check [OPTIONS] <COMMAND>")]
 pub struct Config {
-    #[arg(help = "Package name to check")]
-    pub package: String,
+    #[command(subcommand)]
+    #[serde(skip)]
+    pub command: Option<Command>,
+
+    #[arg(required_unless_present_any = ["import_history", "manifest"], help = "Package names to check")]
+    #[serde(skip)]
+    pub packages: Vec<String>,
 
-    #[arg(long, help = "Output format: default, json, field:name")]
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MANIFEST", help = "Also check the dependencies of a package.json or package-lock.json")]
+    pub manifest: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_FORMAT", help = "Output format: default, table, json, yaml, ndjson, csv, tsv, markdown, html, sarif, junit, openmetrics, template:TEXT, field:name")]
     pub format: Option<String>,
 
-    #[arg(long, help = "Load settings from TOML file")]
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_TEMPLATE", help = "Render each package with a template file, see --format template:TEXT")]
+    pub template: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_OUTPUT", help = "Write --format openmetrics to FILE atomically, e.g. for a textfile collector")]
+    pub output: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_SORT", allow_hyphen_values = true, help = "Sort packages by a field, descending with a leading '-', e.g. -downloads_last_week")]
+    pub sort: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_FIELDS", value_delimiter = ',', help = "Fields to output, in order, e.g. name,latest_version,dist_tags.next.version")]
+    pub fields: Vec<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_CONFIG_FILE", help = "Load settings from TOML file")]
     pub config_file: Option<String>,
 
-    #[arg(long, help = "Save/load run history")]
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_PROFILE", help = "Use the settings of a [profile.NAME] section of the config files")]
+    pub profile: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_HISTORY", help = "Save/load run history")]
     pub history: Option<String>,
 
-    #[arg(long, help = "Check field changes (sets exit code)")]
-    pub check: Option<String>,
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_HISTORY_DB", conflicts_with = "history", help = "Save/load run history in a SQLite database")]
+    pub history_db: Option<String>,
+
+    #[arg(long, help = "Import a JSON history file into --history-db (repeatable)")]
+    #[serde(skip)]
+    pub import_history: Vec<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_CHECK", help = "Check field changes, e.g. downloads_last_month:-20% (repeatable, exit code 1 when triggered)")]
+    #[serde(rename = "checks")]
+    pub check: Vec<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_NO_UPDATE_HISTORY", help = "Compare against history without recording this run")]
+    pub no_update_history: bool,
+
+    #[arg(long, conflicts_with = "no_update_history", help = "Record this run as the new baseline even if a check triggered")]
+    pub accept: bool,
 
-    #[arg(long, default_value = "90", help = "Maximum days since last publish (default: 90)")]
-    pub max_days: i64,
+    #[arg(
+        long,
+        env = "NPM_ACTIVITY_CHECK_DIFF",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "default",
+        help = "Show all changes since the stored history: default, json, markdown"
+    )]
+    pub diff: Option<String>,
 
-    #[arg(long, default_value = "1000", help = "Minimum weekly downloads threshold (default: 1000)")]
-    pub min_weekly_downloads: u64,
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_SINCE", help = "Compare --check against history from N days ago instead of the last run")]
+    pub since: Option<i64>,
 
-    #[arg(long, default_value = "5000", help = "Minimum monthly downloads threshold (default: 5000)")]
-    pub min_monthly_downloads: u64,
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_HISTORY_MAX_ENTRIES", help = "Keep at most N history entries")]
+    pub history_max_entries: Option<usize>,
 
-    #[arg(long, default_value = "10", help = "Minimum total versions threshold (default: 10)")]
-    pub min_versions: usize,
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_HISTORY_MAX_DAYS", help = "Drop history entries older than N days")]
+    pub history_max_days: Option<i64>,
 
-    #[arg(long, default_value = "1", help = "Minimum maintainers threshold (default: 1)")]
-    pub min_maintainers: usize,
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_HISTORY_COMPACT_DAYS", help = "Keep one history entry per day for entries older than N days")]
+    pub history_compact_days: Option<i64>,
 
-    #[arg(long, help = "Show detailed output")]
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_TAG", help = "Dist-tag whose releases count as activity (default: latest)")]
+    pub tag: Option<String>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MAX_DAYS", help = "Maximum days since last publish (default: 90)")]
+    pub max_days: Option<i64>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MIN_WEEKLY_DOWNLOADS", help = "Minimum weekly downloads threshold (default: 1000)")]
+    pub min_weekly_downloads: Option<u64>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MIN_MONTHLY_DOWNLOADS", help = "Minimum monthly downloads threshold (default: 5000)")]
+    pub min_monthly_downloads: Option<u64>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MIN_VERSIONS", help = "Minimum total versions threshold (default: 10)")]
+    pub min_versions: Option<usize>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_MIN_MAINTAINERS", help = "Minimum maintainers threshold (default: 1)")]
+    pub min_maintainers: Option<usize>,
+
+    #[arg(long, env = "NPM_ACTIVITY_CHECK_VERBOSE", help = "Show detailed output")]
     pub verbose: bool,
+
+    /// `[packages."name"]` sections from the config files, keyed by name or glob.
+    #[arg(skip)]
+    #[serde(rename = "packages", skip_serializing_if = "BTreeMap::is_empty")]
+    pub package_overrides: BTreeMap<String, PackageOverride>,
+
+    #[arg(skip)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<Ignore>,
+
+    /// Where each setting came from, keyed like the config file. Missing keys are defaults.
+    #[arg(skip)]
+    #[serde(skip)]
+    pub sources: BTreeMap<String, Source>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Profile(String, PathBuf),
+    Env(String),
+    CommandLine,
+}
+
+impl Source {
+    /// Higher wins; a setting without a source is a default and ranks below all of these.
+    fn precedence(&self) -> u8 {
+        match self {
+            Source::File(_) => 1,
+            Source::Profile(..) => 2,
+            Source::Env(_) => 3,
+            Source::CommandLine => 4,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Profile(name, path) => write!(f, "{} [profile.{}]", path.display(), name),
+            Source::Env(name) => write!(f, "env {}", name),
+            Source::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Print the JSON Schema of the `--format json` output
+    Schema,
+    /// Inspect the layered configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the effective settings and where each one came from
+    Show,
+}
+
+/// Thresholds for the packages matching a `[packages."..."]` section.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageOverride {
+    pub tag: Option<String>,
+    pub max_days: Option<i64>,
+    pub min_weekly_downloads: Option<u64>,
+    pub min_monthly_downloads: Option<u64>,
+    pub min_versions: Option<usize>,
+    pub min_maintainers: Option<usize>,
+}
+
+impl PackageOverride {
+    fn apply(&self, thresholds: &mut Thresholds) {
+        if let Some(tag) = &self.tag {
+            thresholds.tag = tag.clone();
+        }
+        thresholds.max_days = self.max_days.unwrap_or(thresholds.max_days);
+        thresholds.min_weekly_downloads = self.min_weekly_downloads.unwrap_or(thresholds.min_weekly_downloads);
+        thresholds.min_monthly_downloads = self.min_monthly_downloads.unwrap_or(thresholds.min_monthly_downloads);
+        thresholds.min_versions = self.min_versions.unwrap_or(thresholds.min_versions);
+        thresholds.min_maintainers = self.min_maintainers.unwrap_or(thresholds.min_maintainers);
+    }
+}
+
+/// A `[profile.NAME]` section, selected with `--profile NAME`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub format: Option<String>,
+    pub fields: Option<Vec<String>>,
+    pub tag: Option<String>,
+    pub max_days: Option<i64>,
+    pub min_weekly_downloads: Option<u64>,
+    pub min_monthly_downloads: Option<u64>,
+    pub min_versions: Option<usize>,
+    pub min_maintainers: Option<usize>,
+}
+
+/// An `[[ignore]]` entry: matching packages are skipped until `expires` has passed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ignore {
+    pub package: String,
+    pub reason: String,
+    pub expires: NaiveDate,
+}
+
+impl Ignore {
+    /// The expiry day itself is still covered.
+    pub fn is_expired(&self) -> bool {
+        self.expires < Utc::now().date_naive()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileConfig {
     pub format: Option<String>,
+    pub fields: Option<Vec<String>>,
+    pub tag: Option<String>,
     pub max_days: Option<i64>,
     pub min_weekly_downloads: Option<u64>,
     pub min_monthly_downloads: Option<u64>,
     pub min_versions: Option<usize>,
     pub min_maintainers: Option<usize>,
+    pub history: Option<String>,
+    pub history_db: Option<String>,
+    pub checks: Option<Vec<String>>,
+    pub history_max_entries: Option<usize>,
+    pub history_max_days: Option<i64>,
+    pub history_compact_days: Option<i64>,
+    #[serde(default)]
+    pub packages: BTreeMap<String, PackageOverride>,
+    #[serde(default)]
+    pub ignore: Vec<Ignore>,
+    #[serde(default)]
+    pub profile: BTreeMap<String, Profile>,
+}
+
+impl FileConfig {
+    /// Lays the profile's settings over the top-level ones, crediting them to the profile.
+    fn with_profile(mut self, name: &str, sources: &mut BTreeMap<String, Source>) -> Result<Self> {
+        let profile = self.profile.get(name).cloned()
+            .with_context(|| format!("Profile '{}' not found in the config files", name))?;
+        let path = match sources.get(&format!("profile.{}", name)) {
+            Some(Source::File(path)) => path.clone(),
+            _ => PathBuf::new(),
+        };
+        let mut from_profile = |key: &str| {
+            sources.insert(key.to_string(), Source::Profile(name.to_string(), path.clone()));
+        };
+
+        if profile.format.is_some() {
+            self.format = profile.format;
+            from_profile("format");
+        }
+        if profile.fields.is_some() {
+            self.fields = profile.fields;
+            from_profile("fields");
+        }
+        if profile.tag.is_some() {
+            self.tag = profile.tag;
+            from_profile("tag");
+        }
+        if profile.max_days.is_some() {
+            self.max_days = profile.max_days;
+            from_profile("max_days");
+        }
+        if profile.min_weekly_downloads.is_some() {
+            self.min_weekly_downloads = profile.min_weekly_downloads;
+            from_profile("min_weekly_downloads");
+        }
+        if profile.min_monthly_downloads.is_some() {
+            self.min_monthly_downloads = profile.min_monthly_downloads;
+            from_profile("min_monthly_downloads");
+        }
+        if profile.min_versions.is_some() {
+            self.min_versions = profile.min_versions;
+            from_profile("min_versions");
+        }
+        if profile.min_maintainers.is_some() {
+            self.min_maintainers = profile.min_maintainers;
+            from_profile("min_maintainers");
+        }
+
+        Ok(self)
+    }
 }
 
 impl Config {
     pub fn validate(&self) -> Result<()> {
-        if self.package.trim().is_empty() {
+        if self.packages.iter().any(|p| p.trim().is_empty()) {
             anyhow::bail!("Package name cannot be empty");
         }
 
+        if self.history.is_some() && self.history_db.is_some() {
+            anyhow::bail!("history and history_db can't both be set");
+        }
+
+        if !self.import_history.is_empty() && self.history_db.is_none() {
+            anyhow::bail!("--import-history requires --history-db");
+        }
+
+        if let Some(history) = &self.history {
+            if self.packages.len() > 1 && !history.contains(PACKAGE_PLACEHOLDER) {
+                anyhow::bail!("--history needs a {} placeholder when checking several packages", PACKAGE_PLACEHOLDER);
+            }
+        }
+
+        for (option, days) in [
+            ("--since", self.since),
+            ("--history-max-days", self.history_max_days),
+            ("--history-compact-days", self.history_compact_days),
+        ] {
+            if days.is_some_and(|days| !(1..=MAX_DAYS).contains(&days)) {
+                anyhow::bail!("{} must be between 1 and {} days", option, MAX_DAYS);
+            }
+        }
+        if self.history_max_entries == Some(0) {
+            anyhow::bail!("--history-max-entries must be at least 1");
+        }
+
+        for field in &self.fields {
+            validate_path(field)?;
+        }
+
+        if let Some(sort) = &self.sort {
+            validate_path(sort.strip_prefix('-').unwrap_or(sort))?;
+            // NDJSON prints each package as soon as it is checked
+            if self.format.as_deref() == Some("ndjson") {
+                anyhow::bail!("--sort can't be combined with --format ndjson");
+            }
+        }
+
+        if !self.fields.is_empty() && self.format.as_deref().is_some_and(|f| f.starts_with("field:")) {
+            anyhow::bail!("--fields can't be combined with --format field:NAME");
+        }
+
+        for check in &self.check {
+            CheckExpr::parse(check)?;
+        }
+
+        for pattern in self.package_overrides.keys() {
+            glob::Pattern::new(pattern)
+                .with_context(|| format!("Invalid package pattern in config file: {}", pattern))?;
+        }
+
+        for ignore in &self.ignore {
+            glob::Pattern::new(&ignore.package)
+                .with_context(|| format!("Invalid package pattern in config file: {}", ignore.package))?;
+            if ignore.reason.trim().is_empty() {
+                anyhow::bail!("Ignore entry for '{}' needs a reason", ignore.package);
+            }
+        }
+
+        if let Some(diff) = &self.diff {
+            if !matches!(diff.as_str(), "default" | "json" | "markdown") {
+                anyhow::bail!("Invalid diff format. Use 'default', 'json', or 'markdown'");
+            }
+        }
+
+        if self.output.is_some() && self.format.as_deref() != Some("openmetrics") {
+            anyhow::bail!("--output is only supported with --format openmetrics");
+        }
+
+        match (self.format.as_deref(), &self.template) {
+            (Some("template"), Some(path)) => {
+                let source = std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read template: {}", path))?;
+                compile_template(&source)?;
+            }
+            (Some("template"), None) => anyhow::bail!("--format template requires --template"),
+            (Some(format), _) if format.starts_with("template:") => compile_template(&format[9..])?,
+            (_, Some(_)) => anyhow::bail!("--template can't be combined with --format other than template"),
+            _ => {}
+        }
+
+        if !self.fields.is_empty() && self.format.as_deref().is_some_and(|f| f.starts_with("template")) {
+            anyhow::bail!("--fields can't be combined with templates, use the fields in the template");
+        }
+
         if let Some(format) = &self.format {
-            if !format.starts_with("field:") && format != "json" && format != "default" {
-                anyhow::bail!("Invalid format. Use 'default', 'json', or 'field:FIELD_NAME'");
+            if !format.starts_with("field:") && !format.starts_with("template:") && !matches!(format.as_str(), "default" | "table" | "json" | "yaml" | "ndjson" | "csv" | "tsv" | "markdown" | "html" | "sarif" | "junit" | "openmetrics" | "template") {
+                anyhow::bail!("Invalid format. Use 'default', 'table', 'json', 'yaml', 'ndjson', 'csv', 'tsv', 'markdown', 'html', 'sarif', 'junit', 'openmetrics', 'template:TEXT', or 'field:FIELD_NAME'");
             }
         }
 
+        // Code scanning only accepts results that point at a file
+        if self.format.as_deref() == Some("sarif") && self.manifest.is_none() {
+            anyhow::bail!("--format sarif requires --manifest");
+        }
+
         Ok(())
     }
 
-    pub fn from_toml(path: &str) -> Result<FileConfig> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {}", path))?;
-        
-        let config: FileConfig = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path))?;
-        
-        Ok(config)
+    /// Parses the command line, remembering which options were given there or
+    /// through `NPM_ACTIVITY_CHECK_*` variables rather than left at their defaults.
+    pub fn from_command_line() -> Self {
+        let matches = Self::command().get_matches();
+        let mut config = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        config.record_sources(&matches);
+        config
     }
 
-    pub fn merge(mut self, file_config: FileConfig) -> Self {
-        if self.format.is_none() {
+    fn record_sources(&mut self, matches: &ArgMatches) {
+        for arg in Self::command().get_arguments() {
+            let id = arg.get_id().as_str();
+            let source = match matches.value_source(id) {
+                Some(ValueSource::CommandLine) => Source::CommandLine,
+                Some(ValueSource::EnvVariable) => {
+                    Source::Env(arg.get_env().unwrap_or_default().to_string_lossy().into_owned())
+                }
+                _ => continue,
+            };
+            // `--check` is `checks` in the config file
+            let key = if id == "check" { "checks" } else { id };
+            self.sources.insert(key.to_string(), source);
+        }
+    }
+
+    /// Config files in increasing precedence: the global one, the nearest
+    /// project one up from the current directory, then `--config-file`.
+    pub fn config_files(&self) -> Vec<PathBuf> {
+        let global = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|dir| dir.join(GLOBAL_CONFIG));
+        let project = std::env::current_dir().ok().and_then(|dir| {
+            dir.ancestors().map(|d| d.join(PROJECT_CONFIG)).find(|path| path.is_file())
+        });
+
+        global.filter(|path| path.is_file())
+            .into_iter()
+            .chain(project)
+            .chain(self.config_file.as_ref().map(PathBuf::from))
+            .collect()
+    }
+
+    /// Layers the files key by key, later files winning, and remembers which file set each key.
+    pub fn load_config_files(paths: &[PathBuf]) -> Result<(FileConfig, BTreeMap<String, Source>)> {
+        let mut merged = toml::Table::new();
+        let mut sources = BTreeMap::new();
+
+        for path in paths {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+            let table: toml::Table = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+            // Type errors are reported against the file that has them
+            FileConfig::deserialize(table.clone())
+                .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+
+            for (key, value) in table {
+                sources.insert(key.clone(), Source::File(path.clone()));
+                // Package and profile sections are layered one by one, like the top-level keys
+                if let (toml::Value::Table(sections), "packages" | "profile") = (&value, key.as_str()) {
+                    for name in sections.keys() {
+                        sources.insert(format!("{}.{}", key, name), Source::File(path.clone()));
+                    }
+                }
+                match (merged.get_mut(&key), value) {
+                    (Some(toml::Value::Table(existing)), toml::Value::Table(sections)) if key == "packages" || key == "profile" => {
+                        existing.extend(sections);
+                    }
+                    (_, value) => {
+                        merged.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        let file_config = FileConfig::deserialize(merged).context("Failed to merge config files")?;
+        Ok((file_config, sources))
+    }
+
+    pub fn merge(mut self, file_config: FileConfig, file_sources: &BTreeMap<String, Source>) -> Result<Self> {
+        let mut file_sources = file_sources.clone();
+        let file_config = match &self.profile {
+            Some(name) => file_config.with_profile(name, &mut file_sources)?,
+            None => file_config,
+        };
+
+        let from_file = |sources: &mut BTreeMap<String, Source>, key: &str| {
+            if let Some(source) = file_sources.get(key) {
+                sources.insert(key.to_string(), source.clone());
+            }
+        };
+
+        if self.format.is_none() && file_config.format.is_some() {
             self.format = file_config.format;
+            from_file(&mut self.sources, "format");
         }
-        if let Some(max_days) = file_config.max_days {
-            self.max_days = max_days;
+        if self.fields.is_empty() && file_config.fields.is_some() {
+            self.fields = file_config.fields.unwrap_or_default();
+            from_file(&mut self.sources, "fields");
         }
-        if let Some(min_weekly) = file_config.min_weekly_downloads {
-            self.min_weekly_downloads = min_weekly;
+        if self.tag.is_none() && file_config.tag.is_some() {
+            self.tag = file_config.tag;
+            from_file(&mut self.sources, "tag");
         }
-        if let Some(min_monthly) = file_config.min_monthly_downloads {
-            self.min_monthly_downloads = min_monthly;
+        if self.max_days.is_none() && file_config.max_days.is_some() {
+            self.max_days = file_config.max_days;
+            from_file(&mut self.sources, "max_days");
         }
-        if let Some(min_versions) = file_config.min_versions {
-            self.min_versions = min_versions;
+        if self.min_weekly_downloads.is_none() && file_config.min_weekly_downloads.is_some() {
+            self.min_weekly_downloads = file_config.min_weekly_downloads;
+            from_file(&mut self.sources, "min_weekly_downloads");
         }
-        if let Some(min_maintainers) = file_config.min_maintainers {
-            self.min_maintainers = min_maintainers;
+        if self.min_monthly_downloads.is_none() && file_config.min_monthly_downloads.is_some() {
+            self.min_monthly_downloads = file_config.min_monthly_downloads;
+            from_file(&mut self.sources, "min_monthly_downloads");
         }
-        
-        self
+        if self.min_versions.is_none() && file_config.min_versions.is_some() {
+            self.min_versions = file_config.min_versions;
+            from_file(&mut self.sources, "min_versions");
+        }
+        if self.min_maintainers.is_none() && file_config.min_maintainers.is_some() {
+            self.min_maintainers = file_config.min_maintainers;
+            from_file(&mut self.sources, "min_maintainers");
+        }
+        // `history` and `history_db` are alternatives, a file can't add one to the other
+        if self.history.is_none() && self.history_db.is_none() {
+            if file_config.history.is_some() {
+                self.history = file_config.history;
+                from_file(&mut self.sources, "history");
+            }
+            if file_config.history_db.is_some() {
+                self.history_db = file_config.history_db;
+                from_file(&mut self.sources, "history_db");
+            }
+        }
+        if self.check.is_empty() && file_config.checks.is_some() {
+            self.check = file_config.checks.unwrap_or_default();
+            from_file(&mut self.sources, "checks");
+        }
+        if self.history_max_entries.is_none() && file_config.history_max_entries.is_some() {
+            self.history_max_entries = file_config.history_max_entries;
+            from_file(&mut self.sources, "history_max_entries");
+        }
+        if self.history_max_days.is_none() && file_config.history_max_days.is_some() {
+            self.history_max_days = file_config.history_max_days;
+            from_file(&mut self.sources, "history_max_days");
+        }
+        if self.history_compact_days.is_none() && file_config.history_compact_days.is_some() {
+            self.history_compact_days = file_config.history_compact_days;
+            from_file(&mut self.sources, "history_compact_days");
+        }
+
+        if !file_config.packages.is_empty() {
+            for name in file_config.packages.keys() {
+                from_file(&mut self.sources, &format!("packages.{}", name));
+            }
+            self.package_overrides = file_config.packages;
+            from_file(&mut self.sources, "packages");
+        }
+        if !file_config.ignore.is_empty() {
+            self.ignore = file_config.ignore;
+            from_file(&mut self.sources, "ignore");
+        }
+
+        Ok(self)
+    }
+
+    /// `config show`: every effective setting as TOML, annotated with its source.
+    pub fn show(&self, files: &[PathBuf]) -> Result<()> {
+        println!("# Precedence: default < global file < project file < --config-file < --profile < environment < command line");
+        for file in files {
+            println!("# Loaded {}", file.display());
+        }
+
+        let table = toml::Table::try_from(self).context("Failed to serialize configuration")?;
+        let source = |key: &str| self.sources.get(key).map_or("default".to_string(), Source::to_string);
+        // `packages` and `ignore` are printed as sections after the plain settings
+        let (sections, settings): (Vec<_>, Vec<_>) = table.into_iter()
+            .partition(|(_, value)| is_section(value));
+
+        let lines: Vec<(String, String)> = settings.iter()
+            .map(|(key, value)| (format!("{} = {}", key, value), source(key)))
+            .collect();
+        let width = lines.iter().map(|(setting, _)| setting.len()).max().unwrap_or(0);
+        for (setting, source) in lines {
+            println!("{:<width$}  # {}", setting, source, width = width);
+        }
+
+        for (key, value) in sections {
+            // Package sections can each come from a different file
+            let parts: Vec<(String, toml::Value)> = match value {
+                toml::Value::Table(table) if key == "packages" => table.into_iter()
+                    .map(|(name, section)| {
+                        let source = source(&format!("packages.{}", name));
+                        (source, toml::Value::Table(toml::Table::from_iter([(name, section)])))
+                    })
+                    .collect(),
+                value => vec![(source(&key), value)],
+            };
+            for (source, value) in parts {
+                println!();
+                println!("# {}", source);
+                let section = toml::Table::from_iter([(key.clone(), value)]);
+                print!("{}", toml::to_string(&section).context("Failed to serialize configuration")?);
+            }
+        }
+        Ok(())
     }
 
     pub fn with_defaults(mut self) -> Self {
+        // `--template` implies `--format template` over a format from a weaker layer,
+        // e.g. `format = "json"` in a project file
+        let precedence = |key: &str| self.sources.get(key).map_or(0, Source::precedence);
+        if self.template.is_some() && precedence("template") > precedence("format") {
+            self.format = None;
+            self.sources.remove("format");
+        }
+
         if self.format.is_none() {
-            self.format = Some("default".to_string());
+            let format = if self.template.is_some() {
+                "template"
+            } else if self.packages.len() > 1 {
+                "table"
+            } else {
+                "default"
+            };
+            self.format = Some(format.to_string());
         }
+
+        let thresholds = self.thresholds();
+        self.tag = Some(thresholds.tag);
+        self.max_days = Some(thresholds.max_days);
+        self.min_weekly_downloads = Some(thresholds.min_weekly_downloads);
+        self.min_monthly_downloads = Some(thresholds.min_monthly_downloads);
+        self.min_versions = Some(thresholds.min_versions);
+        self.min_maintainers = Some(thresholds.min_maintainers);
         self
     }
 
-    pub fn get_package(&self) -> &str {
-        &self.package
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_entries: self.history_max_entries,
+            max_age_days: self.history_max_days,
+            compact_after_days: self.history_compact_days,
+        }
+    }
+
+    /// The thresholds to score with, defaults filling in whatever wasn't set.
+    pub fn thresholds(&self) -> Thresholds {
+        let defaults = Thresholds::default();
+        Thresholds {
+            tag: self.tag.clone().unwrap_or(defaults.tag),
+            max_days: self.max_days.unwrap_or(defaults.max_days),
+            min_weekly_downloads: self.min_weekly_downloads.unwrap_or(defaults.min_weekly_downloads),
+            min_monthly_downloads: self.min_monthly_downloads.unwrap_or(defaults.min_monthly_downloads),
+            min_versions: self.min_versions.unwrap_or(defaults.min_versions),
+            min_maintainers: self.min_maintainers.unwrap_or(defaults.min_maintainers),
+        }
+    }
+
+    /// The thresholds for `package`, after the `[packages."..."]` sections matching it.
+    /// Globs apply from the shortest to the longest, the exact name last.
+    pub fn thresholds_for(&self, package: &str) -> Thresholds {
+        let mut sections: Vec<(&String, &PackageOverride)> = self.package_overrides.iter()
+            .filter(|(pattern, _)| matches_package(pattern, package))
+            .collect();
+        sections.sort_by_key(|(pattern, _)| (pattern.as_str() == package, pattern.len()));
+
+        let mut thresholds = self.thresholds();
+        for (_, section) in sections {
+            section.apply(&mut thresholds);
+        }
+        thresholds
+    }
+
+    /// The first `[[ignore]]` entry matching `package`, expired or not.
+    pub fn ignore_for(&self, package: &str) -> Option<&Ignore> {
+        self.ignore.iter().find(|ignore| matches_package(&ignore.package, package))
+    }
+}
+
+fn matches_package(pattern: &str, package: &str) -> bool {
+    pattern == package || glob::Pattern::new(pattern).is_ok_and(|p| p.matches(package))
+}
+
+// A table, or a non-empty array of tables such as `[[ignore]]`
+fn is_section(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Table(_) => true,
+        toml::Value::Array(items) => !items.is_empty() && items.iter().all(toml::Value::is_table),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Config {
+        let matches = Config::command().get_matches_from(std::iter::once("npm-activity-check").chain(args.iter().copied()));
+        let mut config = Config::from_arg_matches(&matches).unwrap();
+        config.record_sources(&matches);
+        config
+    }
+
+    fn file_config(content: &str) -> (FileConfig, BTreeMap<String, Source>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, content).unwrap();
+        Config::load_config_files(&[path]).unwrap()
+    }
+
+    #[test]
+    fn command_line_template_beats_file_format() {
+        let (file, sources) = file_config("format = \"json\"");
+        let config = parse(&["--template", "t.tmpl", "react"]).merge(file, &sources).unwrap().with_defaults();
+        assert_eq!(config.format.as_deref(), Some("template"));
+
+        let (file, sources) = file_config("format = \"json\"");
+        let config = parse(&["--format", "json", "--template", "t.tmpl", "react"]).merge(file, &sources).unwrap().with_defaults();
+        assert_eq!(config.format.as_deref(), Some("json"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn command_line_thresholds_beat_files() {
+        let (file, sources) = file_config("max_days = 120\nmin_versions = 3");
+        let config = parse(&["--max-days", "30", "react"]).merge(file, &sources).unwrap();
+        let thresholds = config.thresholds();
+        assert_eq!(thresholds.max_days, 30);
+        assert_eq!(thresholds.min_versions, 3);
+        assert_eq!(thresholds.min_maintainers, Thresholds::default().min_maintainers);
+        assert_eq!(config.sources.get("max_days"), Some(&Source::CommandLine));
+        assert!(matches!(config.sources.get("min_versions"), Some(Source::File(_))));
+        assert_eq!(config.sources.get("min_maintainers"), None);
+    }
+
+    #[test]
+    fn environment_beats_files() {
+        // Only this test sets the variable, and no other test looks at the setting
+        std::env::set_var("NPM_ACTIVITY_CHECK_MIN_MONTHLY_DOWNLOADS", "42");
+        let config = parse(&["react"]);
+        std::env::remove_var("NPM_ACTIVITY_CHECK_MIN_MONTHLY_DOWNLOADS");
+
+        let (file, sources) = file_config("min_monthly_downloads = 7");
+        let config = config.merge(file, &sources).unwrap();
+        assert_eq!(config.thresholds().min_monthly_downloads, 42);
+        assert_eq!(
+            config.sources.get("min_monthly_downloads"),
+            Some(&Source::Env("NPM_ACTIVITY_CHECK_MIN_MONTHLY_DOWNLOADS".to_string()))
+        );
+    }
+
+    #[test]
+    fn reads_checks_and_history_from_files() {
+        let (file, sources) = file_config("checks = [\"latest_version:major\"]\nhistory = \"h.json\"");
+        let config = parse(&["react"]).merge(file, &sources).unwrap();
+        assert_eq!(config.check, ["latest_version:major"]);
+        assert_eq!(config.history.as_deref(), Some("h.json"));
+
+        let (file, sources) = file_config("checks = [\"latest_version\"]\nhistory = \"h.json\"");
+        let config = parse(&["--check", "name", "--history-db", "h.db", "react"]).merge(file, &sources).unwrap();
+        assert_eq!(config.check, ["name"]);
+        assert_eq!(config.history, None);
+        assert_eq!(config.history_db.as_deref(), Some("h.db"));
+    }
+
+    #[test]
+    fn later_files_win_key_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.toml");
+        let project = dir.path().join("project.toml");
+        std::fs::write(&global, "max_days = 10\nmin_versions = 5").unwrap();
+        std::fs::write(&project, "max_days = 20").unwrap();
+
+        let (file, sources) = Config::load_config_files(&[global.clone(), project.clone()]).unwrap();
+        assert_eq!(file.max_days, Some(20));
+        assert_eq!(file.min_versions, Some(5));
+        assert_eq!(sources.get("max_days"), Some(&Source::File(project)));
+        assert_eq!(sources.get("min_versions"), Some(&Source::File(global)));
+    }
+
+    #[test]
+    fn reports_the_file_with_a_type_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.toml");
+        std::fs::write(&path, "max_days = \"soon\"").unwrap();
+        let error = Config::load_config_files(std::slice::from_ref(&path)).unwrap_err();
+        assert!(format!("{:#}", error).contains(&path.display().to_string()));
+    }
+
+    #[test]
+    fn credits_each_package_section_to_its_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let global = dir.path().join("global.toml");
+        let local = dir.path().join("local.toml");
+        std::fs::write(&global, "[packages.\"ms\"]\nmax_days = 3650").unwrap();
+        std::fs::write(&local, "[packages.\"@types/*\"]\nmin_maintainers = 0").unwrap();
+
+        let (file, sources) = Config::load_config_files(&[global.clone(), local.clone()]).unwrap();
+        let config = parse(&["react"]).merge(file, &sources).unwrap();
+        assert_eq!(config.package_overrides.len(), 2);
+        assert_eq!(config.sources.get("packages.ms"), Some(&Source::File(global)));
+        assert_eq!(config.sources.get("packages.@types/*"), Some(&Source::File(local)));
+    }
+
+    #[test]
+    fn rejects_sort_with_ndjson() {
+        assert!(parse(&["--format", "ndjson", "--sort", "name", "react"]).validate().is_err());
+        assert!(parse(&["--format", "json", "--sort", "-downloads_last_week", "react"]).validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_out_of_range_day_counts() {
+        for args in [
+            "--since 0",
+            "--since=-1",
+            "--since 1000000000",
+            "--history-max-days 0",
+            "--history-compact-days 36501",
+            "--history-max-entries 0",
+        ] {
+            let args: Vec<&str> = args.split_whitespace().chain(["react"]).collect();
+            assert!(parse(&args).validate().is_err(), "{:?}", args);
+        }
+        assert!(parse(&["--since", "30", "--history-max-days", "36500", "react"]).validate().is_ok());
+    }
+
+    #[test]
+    fn sarif_requires_manifest() {
+        assert!(parse(&["--format", "sarif", "react"]).validate().is_err());
+        assert!(parse(&["--format", "sarif", "--manifest", "package.json"]).validate().is_ok());
+    }
+
+    #[test]
+    fn diff_format_needs_equals() {
+        let config = parse(&["--diff", "react"]);
+        assert_eq!(config.diff.as_deref(), Some("default"));
+        assert_eq!(config.packages, ["react"]);
+        assert_eq!(parse(&["--diff=json", "react"]).diff.as_deref(), Some("json"));
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::types::PackageReport;
+use crate::version::{is_version_field, VersionChange};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDiff {
+    pub package: String,
+    pub baseline_recorded_at: Option<DateTime<Utc>>,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+    pub delta: Delta,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Delta {
+    Number {
+        change: i64,
+        absolute: u64,
+        percent: Option<f64>,
+    },
+    List {
+        added: Vec<Value>,
+        removed: Vec<Value>,
+    },
+    Duration {
+        seconds: i64,
+        days: i64,
+    },
+    Version(VersionChange),
+    Value,
+}
+
+impl ReportDiff {
+    /// Compares every field of `current` against `baseline`.
+    /// `published` lists all versions of the package, for version classification.
+    pub fn between(
+        baseline: Option<(&PackageReport, DateTime<Utc>)>,
+        current: &PackageReport,
+        published: &[String],
+    ) -> Self {
+        let changes = match baseline {
+            Some((old, _)) => {
+                let (mut old_object, mut new_object) = (to_object(old), to_object(current));
+                // History recorded before dist-tags were tracked is no baseline for them
+                if old.dist_tags.is_none() {
+                    old_object.remove("dist_tags");
+                    new_object.remove("dist_tags");
+                }
+                diff_objects(&old_object, &new_object, published)
+            }
+            None => Vec::new(),
+        };
+
+        Self {
+            package: current.name.clone(),
+            baseline_recorded_at: baseline.map(|(_, recorded_at)| recorded_at),
+            changes,
+        }
+    }
+}
+
+fn to_object(report: &PackageReport) -> Map<String, Value> {
+    match serde_json::to_value(report) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+fn diff_objects(old: &Map<String, Value>, new: &Map<String, Value>, published: &[String]) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    collect_changes("", old, new, published, &mut changes);
+    changes
+}
+
+// Nested objects such as `dist_tags` are compared per key, e.g. `dist_tags.next.version`
+fn collect_changes(
+    prefix: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    published: &[String],
+    changes: &mut Vec<FieldChange>,
+) {
+    let removed_keys = old.keys().filter(|k| !new.contains_key(*k));
+
+    for key in new.keys().chain(removed_keys) {
+        let field = format!("{}{}", prefix, key);
+        let old_value = old.get(key).cloned().unwrap_or(Value::Null);
+        let new_value = new.get(key).cloned().unwrap_or(Value::Null);
+        if old_value == new_value {
+            continue;
+        }
+
+        if let (Value::Object(old_map), Value::Object(new_map)) = (&old_value, &new_value) {
+            collect_changes(&format!("{}.", field), old_map, new_map, published, changes);
+            continue;
+        }
+
+        let delta = match (old_value.as_str(), new_value.as_str()) {
+            (Some(o), Some(n)) if is_version_field(&field) => VersionChange::between(o, n, published)
+                .map_or(Delta::Value, Delta::Version),
+            _ => delta(&old_value, &new_value),
+        };
+        changes.push(FieldChange {
+            field,
+            delta,
+            old: old_value,
+            new: new_value,
+        });
+    }
+}
+
+fn delta(old: &Value, new: &Value) -> Delta {
+    match (old, new) {
+        (Value::Number(o), Value::Number(n)) => match (o.as_i64(), n.as_i64()) {
+            (Some(o), Some(n)) => Delta::Number {
+                change: n - o,
+                absolute: n.abs_diff(o),
+                percent: (o != 0).then(|| (n - o) as f64 / o.abs() as f64 * 100.0),
+            },
+            _ => Delta::Value,
+        },
+        (Value::Array(_), _) | (_, Value::Array(_)) => {
+            let old_items = old.as_array().map(Vec::as_slice).unwrap_or_default();
+            let new_items = new.as_array().map(Vec::as_slice).unwrap_or_default();
+            Delta::List {
+                added: new_items.iter().filter(|v| !old_items.contains(v)).cloned().collect(),
+                removed: old_items.iter().filter(|v| !new_items.contains(v)).cloned().collect(),
+            }
+        }
+        (Value::String(o), Value::String(n)) => match (parse_date(o), parse_date(n)) {
+            (Some(o), Some(n)) => {
+                let duration = n - o;
+                Delta::Duration {
+                    seconds: duration.num_seconds(),
+                    days: duration.num_days(),
+                }
+            }
+            _ => Delta::Value,
+        },
+        _ => Delta::Value,
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|d| d.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DistTag;
+    use std::collections::BTreeMap;
+
+    fn fields(diff: &ReportDiff) -> Vec<&str> {
+        diff.changes.iter().map(|change| change.field.as_str()).collect()
+    }
+
+    #[test]
+    fn history_without_dist_tags_is_no_baseline_for_them() {
+        let mut old = PackageReport { downloads_last_week: 10, ..Default::default() };
+        let mut new = PackageReport { downloads_last_week: 20, ..Default::default() };
+        new.dist_tags = Some(BTreeMap::from([("next".to_string(), DistTag { version: "2.0.0".to_string(), published: None })]));
+
+        let diff = ReportDiff::between(Some((&old, Utc::now())), &new, &[]);
+        assert_eq!(fields(&diff), ["downloads_last_week"]);
+
+        old.dist_tags = Some(BTreeMap::new());
+        let diff = ReportDiff::between(Some((&old, Utc::now())), &new, &[]);
+        assert_eq!(fields(&diff), ["dist_tags.next", "downloads_last_week"]);
+    }
+}
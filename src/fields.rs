@@ -0,0 +1,124 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::types::PackageReport;
+
+/// One step of a field path such as `maintainers[0].name`.
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Every report field is addressable by its serialized name, so fields added
+/// to `PackageReport` become available without being registered here.
+pub fn report_value(report: &PackageReport) -> Value {
+    serde_json::to_value(report).unwrap_or(Value::Null)
+}
+
+/// Top-level field names, in report order.
+pub fn field_names(report: &PackageReport) -> Vec<String> {
+    match report_value(report) {
+        Value::Object(map) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Resolves a path like `dist_tags.next.version` or `maintainers[0].name`.
+pub fn get_field_value(report: &PackageReport, path: &str) -> Option<Value> {
+    resolve(&report_value(report), path)
+}
+
+pub fn resolve(value: &Value, path: &str) -> Option<Value> {
+    let mut current = value;
+    for segment in parse_path(path).ok()? {
+        current = match segment {
+            Segment::Key(key) => current.as_object()?.get(&key)?,
+            Segment::Index(index) => current.as_array()?.get(index)?,
+        };
+    }
+    Some(current.clone())
+}
+
+/// Orders the values of one field across reports: numbers numerically,
+/// strings (and so RFC 3339 dates) lexically, `false` before `true`.
+pub fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => {
+            a.as_f64().unwrap_or_default().total_cmp(&b.as_f64().unwrap_or_default())
+        }
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a.len().cmp(&b.len()),
+        _ => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+/// Checks the syntax of a path, without resolving it.
+pub fn validate_path(path: &str) -> Result<()> {
+    parse_path(path).map(|_| ())
+}
+
+fn parse_path(path: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+
+    for part in path.split('.') {
+        let (key, mut rest) = match part.find('[') {
+            Some(bracket) => part.split_at(bracket),
+            None => (part, ""),
+        };
+        if key.is_empty() && (segments.is_empty() || rest.is_empty()) {
+            anyhow::bail!("Invalid field path: '{}'", path);
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+
+        while !rest.is_empty() {
+            let close = rest.find(']')
+                .filter(|_| rest.starts_with('['))
+                .ok_or_else(|| anyhow::anyhow!("Invalid field path: '{}'", path))?;
+            let index = rest[1..close].parse::<usize>()
+                .map_err(|_| anyhow::anyhow!("Invalid index in field path: '{}'", path))?;
+            segments.push(Segment::Index(index));
+            rest = &rest[close + 1..];
+        }
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(name: &str) -> Segment {
+        Segment::Key(name.to_string())
+    }
+
+    #[test]
+    fn parses_paths() {
+        assert_eq!(parse_path("name").unwrap(), vec![key("name")]);
+        assert_eq!(parse_path("dist_tags.next.version").unwrap(), vec![key("dist_tags"), key("next"), key("version")]);
+        assert_eq!(parse_path("maintainers[0].name").unwrap(), vec![key("maintainers"), Segment::Index(0), key("name")]);
+        assert_eq!(parse_path("a[1][2]").unwrap(), vec![key("a"), Segment::Index(1), Segment::Index(2)]);
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        for path in ["", ".name", "a..b", "[0]", "a[", "a[x]", "a[-1]", "a[0]b", "a.b."] {
+            assert!(parse_path(path).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn resolves_paths() {
+        let value = json!({ "maintainers": [{ "name": "ada" }], "dist_tags": { "next": { "version": "2.0.0" } } });
+        assert_eq!(resolve(&value, "maintainers[0].name"), Some(json!("ada")));
+        assert_eq!(resolve(&value, "dist_tags.next.version"), Some(json!("2.0.0")));
+        assert_eq!(resolve(&value, "maintainers[1].name"), None);
+        assert_eq!(resolve(&value, "dist_tags.beta"), None);
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Path next to `path` with `suffix` appended to the file name, e.g. `history.json.bak`.
+pub fn sibling_path(path: impl AsRef<Path>, suffix: &str) -> PathBuf {
+    let path = path.as_ref();
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Writes to a temporary file in the same directory and renames it over `path`,
+/// so readers see either the old or the new content, never a partial write.
+pub fn write_atomic(path: impl AsRef<Path>, content: &[u8]) -> Result<()> {
+    let path = path.as_ref();
+    let tmp_path = sibling_path(path, &format!(".tmp.{}", std::process::id()));
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+
+    result.with_context(|| format!("Failed to write file: {}", path.display()))
+}
+
+/// Takes an exclusive advisory lock on `<path>.lock`, waiting for other holders.
+/// The lock is released when the returned file is dropped.
+pub fn lock_exclusive(path: &str) -> Result<File> {
+    let lock_path = sibling_path(path, ".lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))?;
+
+    file.lock()
+        .with_context(|| format!("Failed to lock file: {}", lock_path.display()))?;
+
+    Ok(file)
+}
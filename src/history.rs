@@ -1,14 +1,87 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
 use std::path::Path;
 
+use crate::fsutil::{lock_exclusive, sibling_path, write_atomic};
 use crate::types::PackageReport;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub recorded_at: DateTime<Utc>,
+    pub report: PackageReport,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HistoryData {
-    pub last_data: PackageReport,
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Storage backend for run history, keyed by package name.
+pub trait HistoryStore {
+    fn load(&mut self, package: &str) -> Result<HistoryData>;
+    fn record(&mut self, package: &str, entry: HistoryEntry, policy: &RetentionPolicy) -> Result<()>;
+    /// Called once the package is done, whether or not it was recorded.
+    fn release(&mut self, _package: &str) {}
+}
+
+/// One JSON file per package. A `{package}` placeholder in the path is
+/// replaced by the package name, so one template can serve many packages.
+///
+/// Each file is locked from its first `load` until the package is released, so
+/// concurrent runs cannot interleave their load-modify-save cycles. Releasing
+/// closes the lock file, keeping one descriptor open however many packages run.
+pub struct JsonHistoryStore {
+    path: String,
+    verbose: bool,
+    locks: HashMap<String, File>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    pub max_entries: Option<usize>,
+    pub max_age_days: Option<i64>,
+    pub compact_after_days: Option<i64>,
+}
+
+/// Replaced by the package name in `--history` paths.
+pub const PACKAGE_PLACEHOLDER: &str = "{package}";
+
+/// Version of the history file layout written by this build.
+pub const HISTORY_SCHEMA_VERSION: u64 = 2;
+
+// MIGRATIONS[n] upgrades a file from schema version n + 1 to n + 2. The second
+// argument is the file's modification time, for formats that lack timestamps.
+type Migration = fn(Value, DateTime<Utc>) -> Result<Value>;
+const MIGRATIONS: &[Migration] = &[migrate_v1_snapshot_to_series];
+
+#[derive(Serialize)]
+struct HistoryFile<'a> {
+    schema_version: u64,
+    entries: &'a [HistoryEntry],
+}
+
+#[derive(Debug)]
+pub struct NewerSchemaError {
+    pub path: String,
+    pub version: u64,
+}
+
+impl std::fmt::Display for NewerSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "History file {} has schema version {}, but this version of npm-activity-check only supports up to {}. Please upgrade npm-activity-check.",
+            self.path, self.version, HISTORY_SCHEMA_VERSION
+        )
+    }
 }
 
+impl std::error::Error for NewerSchemaError {}
+
 impl HistoryData {
     pub fn load(path: &str, verbose: bool) -> Result<Option<Self>> {
         if !Path::new(path).exists() {
@@ -18,24 +91,87 @@ impl HistoryData {
             return Ok(None);
         }
 
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read history file: {}", path))?;
-
-        let history: HistoryData = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse history file: {}", path))?;
+        let history = match Self::read(Path::new(path), verbose) {
+            Ok(history) => history,
+            // A newer file is intact, falling back to an older backup would lose data
+            Err(err) if err.is::<NewerSchemaError>() => return Err(err),
+            Err(err) => {
+                let backup = sibling_path(path, ".bak");
+                match Self::read(&backup, verbose) {
+                    Ok(history) => {
+                        eprintln!("Warning: {:#}", err);
+                        eprintln!("Warning: Recovered history from backup: {}", backup.display());
+                        history
+                    }
+                    Err(_) => return Err(err),
+                }
+            }
+        };
 
         if verbose {
-            eprintln!("[VERBOSE] Loaded history from: {}", path);
+            eprintln!("[VERBOSE] Loaded {} history entries from: {}", history.entries.len(), path);
         }
 
         Ok(Some(history))
     }
 
+    fn read(path: &Path, verbose: bool) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read history file: {}", path.display()))?;
+
+        let mut value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse history file: {}", path.display()))?;
+
+        let version = schema_version(&value)
+            .with_context(|| format!("Unrecognized history file format: {}", path.display()))?;
+
+        // Versions start at 1; migrations are indexed from there
+        if version == 0 {
+            anyhow::bail!("History file {} has invalid schema version 0", path.display());
+        }
+
+        if version > HISTORY_SCHEMA_VERSION {
+            return Err(NewerSchemaError { path: path.display().to_string(), version }.into());
+        }
+
+        if version < HISTORY_SCHEMA_VERSION {
+            let modified = std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now());
+            for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+                value = migration(value, modified).with_context(|| {
+                    format!("Failed to migrate history file {} from schema version {}", path.display(), from + 1)
+                })?;
+            }
+            if verbose {
+                eprintln!(
+                    "[VERBOSE] Migrated history file {} from schema version {} to {}",
+                    path.display(), version, HISTORY_SCHEMA_VERSION
+                );
+            }
+        }
+
+        serde_json::from_value(value)
+            .with_context(|| format!("Failed to parse history file: {}", path.display()))
+    }
+
+    /// Writes atomically, keeping the previous readable file as `<path>.bak`.
     pub fn save(&self, path: &str, verbose: bool) -> Result<()> {
-        let content = serde_json::to_string_pretty(self)
+        let file = HistoryFile {
+            schema_version: HISTORY_SCHEMA_VERSION,
+            entries: &self.entries,
+        };
+        let content = serde_json::to_string_pretty(&file)
             .context("Failed to serialize history data")?;
 
-        std::fs::write(path, content)
+        if Self::read(Path::new(path), false).is_ok() {
+            let previous = std::fs::read(path)
+                .with_context(|| format!("Failed to read history file: {}", path))?;
+            write_atomic(sibling_path(path, ".bak"), &previous)?;
+        }
+
+        write_atomic(path, content.as_bytes())
             .with_context(|| format!("Failed to write history file: {}", path))?;
 
         if verbose {
@@ -45,72 +181,296 @@ impl HistoryData {
         Ok(())
     }
 
-    pub fn calculate_change(&self, current_report: &PackageReport, field_name: &str) -> Result<u64> {
-        match field_name {
-            "total_versions" => {
-                let old_count = self.last_data.total_versions;
-                let new_count = current_report.total_versions;
-                Ok(if new_count > old_count { new_count - old_count } else { 0 } as u64)
-            }
-            "downloads_last_week" => {
-                let old_downloads = self.last_data.downloads_last_week;
-                let new_downloads = current_report.downloads_last_week;
-                Ok(if new_downloads > old_downloads { 
-                    new_downloads - old_downloads 
-                } else if old_downloads > new_downloads {
-                    old_downloads - new_downloads
-                } else { 
-                    0 
-                })
-            }
-            "downloads_last_month" => {
-                let old_downloads = self.last_data.downloads_last_month;
-                let new_downloads = current_report.downloads_last_month;
-                Ok(if new_downloads > old_downloads { 
-                    new_downloads - old_downloads 
-                } else if old_downloads > new_downloads {
-                    old_downloads - new_downloads
-                } else { 
-                    0 
-                })
-            }
-            "maintainers_count" => {
-                let old_count = self.last_data.maintainers_count;
-                let new_count = current_report.maintainers_count;
-                Ok(if new_count != old_count { 1 } else { 0 })
-            }
-            "latest_version" => {
-                Ok(if self.last_data.latest_version != current_report.latest_version { 1 } else { 0 })
-            }
-            "package_alive" => {
-                Ok(if self.last_data.package_alive != current_report.package_alive { 1 } else { 0 })
-            }
-            "has_recent_activity" => {
-                Ok(if self.last_data.has_recent_activity != current_report.has_recent_activity { 1 } else { 0 })
-            }
-            "last_publish_date" => {
-                let days_diff = (current_report.last_publish_date - self.last_data.last_publish_date).num_days().abs();
-                Ok(days_diff as u64)
-            }
-            "name" => {
-                Ok(if self.last_data.name != current_report.name { 1 } else { 0 })
-            }
-            "description" => {
-                Ok(if self.last_data.description != current_report.description { 1 } else { 0 })
-            }
-            "homepage" => {
-                Ok(if self.last_data.homepage != current_report.homepage { 1 } else { 0 })
-            }
-            "repository_url" => {
-                Ok(if self.last_data.repository_url != current_report.repository_url { 1 } else { 0 })
-            }
-            "license" => {
-                Ok(if self.last_data.license != current_report.license { 1 } else { 0 })
+    pub fn append(&mut self, report: PackageReport, recorded_at: DateTime<Utc>) {
+        self.entries.push(HistoryEntry { recorded_at, report });
+        self.entries.sort_by_key(|e| e.recorded_at);
+    }
+
+    pub fn latest(&self) -> Option<&HistoryEntry> {
+        self.entries.last()
+    }
+
+    /// Most recent entry recorded at or before `when`.
+    pub fn at(&self, when: DateTime<Utc>) -> Option<&HistoryEntry> {
+        self.entries.iter().rev().find(|e| e.recorded_at <= when)
+    }
+
+    /// Entry to compare against: the previous run, or the state `since_days` ago.
+    pub fn baseline(&self, since_days: Option<i64>) -> Option<&HistoryEntry> {
+        match since_days {
+            Some(days) => self.at(days_before(Utc::now(), days)),
+            None => self.latest(),
+        }
+    }
+
+    pub fn apply_retention(&mut self, policy: &RetentionPolicy, now: DateTime<Utc>) {
+        if let Some(days) = policy.compact_after_days {
+            self.compact(days_before(now, days));
+        }
+        if let Some(days) = policy.max_age_days {
+            let cutoff = days_before(now, days);
+            self.entries.retain(|e| e.recorded_at >= cutoff);
+        }
+        if let Some(max_entries) = policy.max_entries {
+            let excess = self.entries.len().saturating_sub(max_entries);
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Keeps only the last entry of each day for entries recorded before `cutoff`.
+    pub fn compact(&mut self, cutoff: DateTime<Utc>) {
+        let mut compacted: Vec<HistoryEntry> = Vec::with_capacity(self.entries.len());
+        for entry in self.entries.drain(..) {
+            if let Some(previous) = compacted.last_mut() {
+                if entry.recorded_at < cutoff
+                    && previous.recorded_at.date_naive() == entry.recorded_at.date_naive()
+                {
+                    *previous = entry;
+                    continue;
+                }
             }
-            "keywords" => {
-                Ok(if self.last_data.keywords != current_report.keywords { 1 } else { 0 })
+            compacted.push(entry);
+        }
+        self.entries = compacted;
+    }
+}
+
+// Saturates at the earliest representable time instead of panicking on huge day counts
+fn days_before(now: DateTime<Utc>, days: i64) -> DateTime<Utc> {
+    Duration::try_days(days)
+        .and_then(|duration| now.checked_sub_signed(duration))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+impl JsonHistoryStore {
+    pub fn new(path: &str, verbose: bool) -> Self {
+        Self { path: path.to_string(), verbose, locks: HashMap::new() }
+    }
+
+    /// History file of `package`; scoped names like `@types/node` become `@types__node`.
+    pub fn path_for(&self, package: &str) -> String {
+        self.path.replace(PACKAGE_PLACEHOLDER, &package.replace('/', "__"))
+    }
+
+    fn lock(&mut self, path: &str) -> Result<()> {
+        if !self.locks.contains_key(path) {
+            self.locks.insert(path.to_string(), lock_exclusive(path)?);
+            if self.verbose {
+                eprintln!("[VERBOSE] Locked history file: {}", path);
             }
-            _ => anyhow::bail!("Unknown field for change calculation: {}", field_name),
         }
+        Ok(())
+    }
+}
+
+impl HistoryStore for JsonHistoryStore {
+    fn load(&mut self, package: &str) -> Result<HistoryData> {
+        let path = self.path_for(package);
+        self.lock(&path)?;
+        Ok(HistoryData::load(&path, self.verbose)?.unwrap_or_default())
+    }
+
+    fn record(&mut self, package: &str, entry: HistoryEntry, policy: &RetentionPolicy) -> Result<()> {
+        let path = self.path_for(package);
+        self.lock(&path)?;
+        let mut history = HistoryData::load(&path, self.verbose)?.unwrap_or_default();
+        history.append(entry.report, entry.recorded_at);
+        history.apply_retention(policy, entry.recorded_at);
+        history.save(&path, self.verbose)
+    }
+
+    fn release(&mut self, package: &str) {
+        let path = self.path_for(package);
+        if self.locks.remove(&path).is_some() && self.verbose {
+            eprintln!("[VERBOSE] Unlocked history file: {}", path);
+        }
+    }
+}
+
+// Files written before `schema_version` existed are recognized by their shape
+fn schema_version(value: &Value) -> Option<u64> {
+    if let Some(version) = value.get("schema_version") {
+        return version.as_u64();
+    }
+    if value.get("last_data").is_some() {
+        Some(1)
+    } else if value.get("entries").is_some() {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+// v1 held a single untimestamped `last_data` report
+fn migrate_v1_snapshot_to_series(mut value: Value, modified: DateTime<Utc>) -> Result<Value> {
+    let report = value.get_mut("last_data")
+        .map(Value::take)
+        .context("Missing 'last_data'")?;
+
+    Ok(json!({
+        "schema_version": 2,
+        "entries": [{ "recorded_at": modified, "report": report }],
+    }))
+}
+
+pub fn calculate_change(old: &PackageReport, new: &PackageReport, field_name: &str) -> Result<u64> {
+    match field_name {
+        "total_versions" => {
+            Ok(new.total_versions.saturating_sub(old.total_versions) as u64)
+        }
+        "downloads_last_week" => {
+            Ok(new.downloads_last_week.abs_diff(old.downloads_last_week))
+        }
+        "downloads_last_month" => {
+            Ok(new.downloads_last_month.abs_diff(old.downloads_last_month))
+        }
+        "maintainers_count" => {
+            Ok(if new.maintainers_count != old.maintainers_count { 1 } else { 0 })
+        }
+        "latest_version" => {
+            Ok(if old.latest_version != new.latest_version { 1 } else { 0 })
+        }
+        "package_alive" => {
+            Ok(if old.package_alive != new.package_alive { 1 } else { 0 })
+        }
+        "has_recent_activity" => {
+            Ok(if old.has_recent_activity != new.has_recent_activity { 1 } else { 0 })
+        }
+        "last_publish_date" => {
+            let days_diff = (new.last_publish_date - old.last_publish_date).num_days().abs();
+            Ok(days_diff as u64)
+        }
+        "name" => {
+            Ok(if old.name != new.name { 1 } else { 0 })
+        }
+        "description" => {
+            Ok(if old.description != new.description { 1 } else { 0 })
+        }
+        "homepage" => {
+            Ok(if old.homepage != new.homepage { 1 } else { 0 })
+        }
+        "repository_url" => {
+            Ok(if old.repository_url != new.repository_url { 1 } else { 0 })
+        }
+        "license" => {
+            Ok(if old.license != new.license { 1 } else { 0 })
+        }
+        "keywords" => {
+            Ok(if old.keywords != new.keywords { 1 } else { 0 })
+        }
+        "dist_tags" => {
+            // Number of tags that were added, removed or moved; nothing without a baseline
+            let (Some(old_tags), Some(new_tags)) = (&old.dist_tags, &new.dist_tags) else {
+                return Ok(0);
+            };
+            let moved = new_tags.iter()
+                .filter(|(tag, dist_tag)| old_tags.get(*tag) != Some(*dist_tag))
+                .count();
+            let removed = old_tags.keys()
+                .filter(|tag| !new_tags.contains_key(*tag))
+                .count();
+            Ok((moved + removed) as u64)
+        }
+        _ => anyhow::bail!("Unknown field for change calculation: {}", field_name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(content: &str) -> Result<HistoryData> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.json");
+        std::fs::write(&path, content).unwrap();
+        HistoryData::read(&path, false)
+    }
+
+    #[test]
+    fn migrates_v1_snapshot() {
+        let history = read(r#"{ "last_data": { "name": "demo", "latest_version": "1.0.0" } }"#).unwrap();
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].report.name, "demo");
+        assert_eq!(history.entries[0].report.latest_version, "1.0.0");
+    }
+
+    #[test]
+    fn reads_current_and_unversioned_series() {
+        let entry = r#"{ "recorded_at": "2026-01-01T00:00:00Z", "report": { "name": "demo" } }"#;
+        for content in [
+            format!(r#"{{ "schema_version": 2, "entries": [{}] }}"#, entry),
+            format!(r#"{{ "entries": [{}] }}"#, entry),
+        ] {
+            assert_eq!(read(&content).unwrap().entries.len(), 1);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(read(r#"{ "schema_version": 0, "entries": [] }"#).is_err());
+        assert!(read(r#"{ "something": "else" }"#).is_err());
+
+        let newer = read(r#"{ "schema_version": 99, "entries": [] }"#).unwrap_err();
+        assert!(newer.is::<NewerSchemaError>());
+    }
+
+    fn series(timestamps: &[&str]) -> HistoryData {
+        let mut history = HistoryData::default();
+        for (i, timestamp) in timestamps.iter().enumerate() {
+            let report = PackageReport { latest_version: format!("1.0.{}", i), ..Default::default() };
+            history.append(report, timestamp.parse().unwrap());
+        }
+        history
+    }
+
+    fn versions(history: &HistoryData) -> Vec<&str> {
+        history.entries.iter().map(|e| e.report.latest_version.as_str()).collect()
+    }
+
+    #[test]
+    fn compacts_to_last_entry_per_day_before_cutoff() {
+        let mut history = series(&[
+            "2026-01-01T08:00:00Z",
+            "2026-01-01T20:00:00Z",
+            "2026-01-02T08:00:00Z",
+            "2026-01-05T08:00:00Z",
+            "2026-01-05T20:00:00Z",
+        ]);
+        history.compact("2026-01-03T00:00:00Z".parse().unwrap());
+        assert_eq!(versions(&history), ["1.0.1", "1.0.2", "1.0.3", "1.0.4"]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn applies_retention_policy() {
+        let now: DateTime<Utc> = "2026-01-10T00:00:00Z".parse().unwrap();
+        let timestamps = [
+            "2026-01-01T08:00:00Z",
+            "2026-01-01T20:00:00Z",
+            "2026-01-08T08:00:00Z",
+            "2026-01-09T08:00:00Z",
+            "2026-01-09T20:00:00Z",
+        ];
+
+        let mut history = series(&timestamps);
+        history.apply_retention(&RetentionPolicy { max_age_days: Some(5), ..Default::default() }, now);
+        assert_eq!(versions(&history), ["1.0.2", "1.0.3", "1.0.4"]);
+
+        let mut history = series(&timestamps);
+        history.apply_retention(&RetentionPolicy { max_entries: Some(2), ..Default::default() }, now);
+        assert_eq!(versions(&history), ["1.0.3", "1.0.4"]);
+
+        let mut history = series(&timestamps);
+        history.apply_retention(&RetentionPolicy { compact_after_days: Some(5), ..Default::default() }, now);
+        assert_eq!(versions(&history), ["1.0.1", "1.0.2", "1.0.3", "1.0.4"]);
+    }
+
+    #[test]
+    fn huge_day_counts_do_not_overflow() {
+        let mut history = series(&["2026-01-01T00:00:00Z"]);
+        assert!(history.baseline(Some(1_000_000_000)).is_none());
+        assert!(history.baseline(Some(i64::MAX)).is_none());
+        let policy = RetentionPolicy { max_age_days: Some(i64::MAX), compact_after_days: Some(i64::MAX), ..Default::default() };
+        history.apply_retention(&policy, Utc::now());
+        assert_eq!(history.entries.len(), 1);
+    }
+}
@@ -1,17 +1,32 @@
 use anyhow::Result;
-use clap::Parser;
+use chrono::Utc;
 
+mod check;
 mod config;
+mod diff;
+mod fields;
+mod fsutil;
 mod history;
+mod manifest;
 mod npm;
 mod output;
 mod scoring;
+mod sqlite_store;
 mod types;
+mod version;
 
-use config::Config;
-use history::HistoryData;
+use check::{evaluate_all, exit_code, fail_all, CheckResult, EXIT_ERROR, EXIT_OK};
+use config::{Command, Config, ConfigCommand};
+use history::{HistoryEntry, HistoryStore, JsonHistoryStore};
+use manifest::Manifest;
 use npm::NpmClient;
-use output::{create_package_report, print_output};
+use output::{
+    create_package_report, print_check_results, print_diffs, print_ndjson, print_output, print_schema, sort_reports,
+    PackageContext,
+};
+use sqlite_store::SqliteHistoryStore;
+use types::PackageReport;
+use version::{VersionChange, VersionChangeKind};
 
 macro_rules! verbose_println {
     ($config:expr, $($arg:tt)*) => {
@@ -23,46 +38,160 @@ macro_rules! verbose_println {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    if let Err(e) = run().await {
-        eprintln!("Error: {e}");
-        
-        let chain = e.chain().skip(1);
-        for cause in chain {
-            eprintln!("  Caused by: {cause}");
+    match run().await {
+        Ok(exit_code) => std::process::exit(exit_code),
+        Err(e) => {
+            print_error(&e);
+            std::process::exit(EXIT_ERROR);
         }
-        
-        std::process::exit(1);
     }
+}
+
+fn print_error(e: &anyhow::Error) {
+    eprintln!("Error: {e}");
     
-    Ok(())
+    let chain = e.chain().skip(1);
+    for cause in chain {
+        eprintln!("  Caused by: {cause}");
+    }
 }
 
-async fn run() -> Result<()> {
-    let config = Config::parse();
+/// Returns the process exit code.
+async fn run() -> Result<i32> {
+    let config = Config::from_command_line();
+
+    if let Some(Command::Schema) = &config.command {
+        print_schema()?;
+        return Ok(EXIT_OK);
+    }
+
+    let config_files = config.config_files();
+    for path in &config_files {
+        verbose_println!(&config, "Loading configuration file: {}", path.display());
+    }
+    let (file_config, file_sources) = Config::load_config_files(&config_files)?;
+    let mut config = config.merge(file_config, &file_sources)?;
+
+    if let Some(Command::Config { action: ConfigCommand::Show }) = &config.command {
+        config.with_defaults().show(&config_files)?;
+        return Ok(EXIT_OK);
+    }
+
+    if let Some(manifest_path) = &config.manifest {
+        let manifest = Manifest::load(manifest_path)?;
+        verbose_println!(&config, "Found {} dependencies in {}", manifest.dependencies.len(), manifest_path);
+        for dependency in manifest.dependencies {
+            if !config.packages.contains(&dependency) {
+                config.packages.push(dependency);
+            }
+        }
+    }
+    // The default format depends on how many packages the manifest added
+    let config = config.with_defaults();
     config.validate()?;
 
-    let config = if let Some(config_path) = &config.config_file {
-        verbose_println!(&config, "Loading configuration file: {}", config_path);
-        let file_config = Config::from_toml(config_path)?;
-        config.merge(file_config).with_defaults()
-    } else {
-        config.with_defaults()
-    };
+    let mut history_store = open_history_store(&config)?;
+
+    if config.packages.is_empty() {
+        return Ok(EXIT_OK);
+    }
+
+    if history_store.is_none() {
+        if !config.check.is_empty() {
+            anyhow::bail!("--check requires --history or --history-db to be specified");
+        } else if config.diff.is_some() {
+            anyhow::bail!("--diff requires --history or --history-db to be specified");
+        }
+    }
 
     let npm_client = NpmClient::new()?;
 
-    verbose_println!(&config, "Fetching package data from NPM registry...");
+    let mut reports = Vec::new();
+    let mut contexts = Vec::new();
+    let mut check_results = Vec::new();
+    let mut failed = false;
+
+    // A failing package is reported and skipped so the others still get checked
+    for package in &config.packages {
+        if let Some(ignore) = config.ignore_for(package) {
+            if !ignore.is_expired() {
+                verbose_println!(&config, "Skipping '{}' until {}: {}", package, ignore.expires, ignore.reason);
+                continue;
+            }
+            eprintln!("Warning: The ignore entry for '{}' expired on {}, checking it again", package, ignore.expires);
+        }
+
+        let store = history_store.as_mut().map(|store| store.as_mut() as &mut dyn HistoryStore);
+        match check_package(&config, &npm_client, store, package).await {
+            Ok((report, results, context)) => {
+                if config.format.as_deref() == Some("ndjson") {
+                    print_ndjson(&config, &report, &results)?;
+                }
+                reports.push(report);
+                contexts.push(context);
+                check_results.extend(results);
+            }
+            Err(e) => {
+                print_error(&e.context(format!("Failed to check package '{}'", package)));
+                failed = true;
+            }
+        }
+        if let Some(store) = history_store.as_mut() {
+            store.release(package);
+        }
+    }
+
+    if let Some(sort) = &config.sort {
+        sort_reports(&mut reports, &mut contexts, sort);
+    }
+
+    if let Some(diff_format) = &config.diff {
+        print_diffs(diff_format, &reports, &contexts, config.since)?;
+    } else if !reports.is_empty() {
+        print_output(&config, &reports, &contexts, &check_results)?;
+    }
+
+    print_check_results(&config, &check_results)?;
+
+    Ok(if failed { EXIT_ERROR } else { exit_code(&check_results) })
+}
+
+async fn check_package(
+    config: &Config,
+    npm_client: &NpmClient,
+    history_store: Option<&mut dyn HistoryStore>,
+    package: &str,
+) -> Result<(PackageReport, Vec<CheckResult>, PackageContext)> {
+    verbose_println!(config, "Fetching package data for '{}' from NPM registry...", package);
     
-    let package_info = npm_client.get_package_info(config.get_package()).await?;
-    let weekly_downloads = npm_client.get_weekly_downloads(config.get_package()).await?;
-    let monthly_downloads = npm_client.get_monthly_downloads(config.get_package()).await?;
+    let package_info = npm_client.get_package_info(package).await?;
+    let weekly_downloads = npm_client.get_weekly_downloads(package).await?;
+    let monthly_downloads = npm_client.get_monthly_downloads(package).await?;
+
+    // Zeros standing in for missing stats must never become a history baseline
+    let downloads_unavailable = weekly_downloads.is_none() || monthly_downloads.is_none();
+    if downloads_unavailable {
+        eprintln!("Warning: Download statistics for '{}' are unavailable, reporting 0 downloads", package);
+    }
+    let weekly_downloads = weekly_downloads.unwrap_or(0);
+    let monthly_downloads = monthly_downloads.unwrap_or(0);
 
     let (latest_version, last_publish_date) = npm_client
-        .get_latest_version_info(&package_info)
+        .get_tag_version_info(&package_info, "latest")
         .ok_or_else(|| anyhow::anyhow!("Could not determine latest version"))?;
 
+    // Most dependencies of a manifest have no pre-release channel, so judge those on `latest`
+    let mut thresholds = config.thresholds_for(package);
+    if npm_client.get_tag_version_info(&package_info, &thresholds.tag).is_none() {
+        eprintln!(
+            "Warning: Dist-tag '{}' not found for package '{}', using 'latest'",
+            thresholds.tag, package
+        );
+        thresholds.tag = "latest".to_string();
+    }
+
     let current_report = create_package_report(
-        &config,
+        &thresholds,
         &package_info,
         &latest_version,
         &last_publish_date,
@@ -70,31 +199,79 @@ async fn run() -> Result<()> {
         monthly_downloads,
     );
 
-    if let Some(history_path) = &config.history {
-        let existing_history = HistoryData::load(history_path, config.verbose)?;
+    let mut published_versions: Vec<String> = package_info.versions.keys().cloned().collect();
+    published_versions.sort_by_key(|version| (package_info.time.get(version).copied(), version.clone()));
 
-        let new_history = HistoryData {
-            last_data: current_report.clone(),
-        };
-        new_history.save(history_path, config.verbose)?;
-
-        if let Some(check_field) = &config.check {
-            verbose_println!(&config, "Checking field '{}' for changes", check_field);
-            
-            if let Some(history) = existing_history {
-                let change_magnitude = history.calculate_change(&current_report, check_field)?;
-                verbose_println!(&config, "Change magnitude for '{}': {}", check_field, change_magnitude);
-                std::process::exit(change_magnitude as i32);
-            } else {
-                verbose_println!(&config, "No history exists, no change to compare (exit code: 0)");
-                std::process::exit(0);
-            }
+    let Some(store) = history_store else {
+        let context = PackageContext { published_versions, ..Default::default() };
+        return Ok((current_report, Vec::new(), context));
+    };
+
+    let mut check_results = Vec::new();
+
+    let history = store.load(package)?;
+    let baseline = history.baseline(config.since).cloned();
+
+    if let Some(baseline) = &baseline {
+        let change = VersionChange::between(
+            &baseline.report.latest_version,
+            &current_report.latest_version,
+            &published_versions,
+        );
+        if change.is_some_and(|c| c.kind == VersionChangeKind::Downgrade) {
+            eprintln!(
+                "Warning: The 'latest' dist-tag of '{}' moved backwards from {} to {}",
+                package, baseline.report.latest_version, current_report.latest_version
+            );
+        }
+    }
+
+    let now = Utc::now();
+
+    if !config.check.is_empty() {
+        if let Some(baseline) = &baseline {
+            verbose_println!(config, "Comparing against history recorded at {}", baseline.recorded_at);
+        } else {
+            verbose_println!(config, "No history exists for '{}', no change to compare", package);
         }
-    } else if config.check.is_some() {
-        anyhow::bail!("--check requires --history to be specified");
+
+        let baseline = baseline.as_ref().map(|b| (&b.report, b.recorded_at));
+        check_results = if downloads_unavailable {
+            fail_all(&config.check, baseline, &current_report, "download statistics unavailable")
+        } else {
+            evaluate_all(&config.check, baseline, &current_report, &published_versions)
+        };
     }
 
-    print_output(&config, &current_report)?;
+    let checks_failed = check_results.iter().any(|r| r.triggered || r.error.is_some());
+    if config.no_update_history {
+        verbose_println!(config, "Not updating history (--no-update-history)");
+    } else if downloads_unavailable {
+        eprintln!("Warning: History of '{}' not updated because download statistics are unavailable", package);
+    } else if checks_failed && !config.accept {
+        eprintln!("Warning: History of '{}' not updated because a check triggered (use --accept to record a new baseline)", package);
+    } else {
+        let entry = HistoryEntry {
+            recorded_at: now,
+            report: current_report.clone(),
+        };
+        store.record(package, entry, &config.retention_policy())?;
+    }
+
+    Ok((current_report, check_results, PackageContext { history, published_versions }))
+}
+
+fn open_history_store(config: &Config) -> Result<Option<Box<dyn HistoryStore>>> {
+    if let Some(db_path) = &config.history_db {
+        let mut store = SqliteHistoryStore::open(db_path, config.verbose)?;
+        for json_path in &config.import_history {
+            let imported = store.import_json(json_path)?;
+            eprintln!("Imported {} history entries from {}", imported, json_path);
+        }
+        return Ok(Some(Box::new(store)));
+    }
 
-    Ok(())
+    Ok(config.history.as_ref().map(|path| {
+        Box::new(JsonHistoryStore::new(path, config.verbose)) as Box<dyn HistoryStore>
+    }))
 }
@@ -0,0 +1,266 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+const DEPENDENCY_SECTIONS: &[&str] = &[
+    "dependencies",
+    "devDependencies",
+    "peerDependencies",
+    "optionalDependencies",
+];
+
+/// The dependencies declared in a `package.json`, or installed according to a
+/// `package-lock.json` / `npm-shrinkwrap.json`.
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub path: String,
+    pub dependencies: Vec<String>,
+    lines: HashMap<String, usize>,
+}
+
+impl Manifest {
+    pub fn load(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest: {}", path))?;
+        let json: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse manifest: {}", path))?;
+
+        let file_name = Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let is_lockfile = matches!(file_name, "package-lock.json" | "npm-shrinkwrap.json");
+
+        let mut dependencies = if is_lockfile {
+            lockfile_dependencies(&json)
+        } else {
+            DEPENDENCY_SECTIONS.iter()
+                .filter_map(|section| json.get(section).and_then(Value::as_object))
+                .flat_map(|deps| deps.keys().cloned())
+                .collect()
+        };
+        dedup(&mut dependencies);
+
+        let layout = match (is_lockfile, json.get("packages").is_some()) {
+            (false, _) => Layout::PackageJson,
+            (true, true) => Layout::LockfilePackages,
+            (true, false) => Layout::LockfileDependencies,
+        };
+        let mut lines = HashMap::new();
+        for (parents, key, line) in object_keys(&content) {
+            if let Some(package) = declared_package(&parents, &key, layout) {
+                lines.entry(package.to_string()).or_insert(line);
+            }
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            dependencies,
+            lines,
+        })
+    }
+
+    /// 1-based line on which `package` is declared, for pointing findings at it.
+    /// In a lockfile that is its first install path, e.g. `node_modules/a/node_modules/b`.
+    pub fn line_of(&self, package: &str) -> Option<usize> {
+        self.lines.get(package).copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    PackageJson,
+    /// lockfileVersion 2 and 3: `packages` keyed by install path. Version 2 also
+    /// keeps the v1 `dependencies` for older npm, which are ignored.
+    LockfilePackages,
+    /// lockfileVersion 1: `dependencies`, nested under each entry as `dependencies` again
+    LockfileDependencies,
+}
+
+// The package a key declares, given the keys of the objects around it. Only keys
+// in dependency sections count, not scripts, tool configs or `requires` entries.
+fn declared_package<'a>(parents: &[String], key: &'a str, layout: Layout) -> Option<&'a str> {
+    match layout {
+        Layout::PackageJson => {
+            let in_section = matches!(parents, [section] if DEPENDENCY_SECTIONS.contains(&section.as_str()));
+            in_section.then_some(key)
+        }
+        Layout::LockfilePackages => match parents {
+            [section] if section == "packages" => key.rsplit_once("node_modules/").map(|(_, name)| name),
+            _ => None,
+        },
+        Layout::LockfileDependencies => {
+            let nested = parents.len() % 2 == 1
+                && parents.iter().step_by(2).all(|parent| parent == "dependencies");
+            nested.then_some(key)
+        }
+    }
+}
+
+// Every object key in a JSON document with the keys of its enclosing objects
+// (`[]` for arrays) and its 1-based line. The document is known to be valid.
+fn object_keys(content: &str) -> Vec<(Vec<String>, String, usize)> {
+    let mut keys = Vec::new();
+    // Per open container: whether it is an object, and the key whose value is being read
+    let mut frames: Vec<(bool, String)> = Vec::new();
+    let mut expect_key = false;
+    let mut line = 1;
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '{' => {
+                frames.push((true, String::new()));
+                expect_key = true;
+            }
+            '[' => {
+                frames.push((false, "[]".to_string()));
+                expect_key = false;
+            }
+            '}' | ']' => {
+                frames.pop();
+                expect_key = false;
+            }
+            ',' => expect_key = frames.last().is_some_and(|(is_object, _)| *is_object),
+            '"' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => text.extend(chars.next()),
+                        c => text.push(c),
+                    }
+                }
+                if expect_key {
+                    let parents = frames[..frames.len() - 1].iter().map(|(_, key)| key.clone()).collect();
+                    keys.push((parents, text.clone(), line));
+                    if let Some(frame) = frames.last_mut() {
+                        frame.1 = text;
+                    }
+                    expect_key = false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    keys
+}
+
+// lockfileVersion 2 and 3 list `packages` by install path, version 1 nests `dependencies`
+fn lockfile_dependencies(json: &Value) -> Vec<String> {
+    if let Some(packages) = json.get("packages").and_then(Value::as_object) {
+        return packages.keys()
+            .filter_map(|key| key.rsplit_once("node_modules/").map(|(_, name)| name.to_string()))
+            .collect();
+    }
+
+    let mut names = Vec::new();
+    collect_v1_dependencies(json, &mut names);
+    names
+}
+
+fn collect_v1_dependencies(json: &Value, names: &mut Vec<String>) {
+    if let Some(deps) = json.get("dependencies").and_then(Value::as_object) {
+        for (name, entry) in deps {
+            names.push(name.clone());
+            collect_v1_dependencies(entry, names);
+        }
+    }
+}
+
+// Keeps the first occurrence, so packages stay in manifest order
+fn dedup(names: &mut Vec<String>) {
+    let mut seen = std::collections::HashSet::new();
+    names.retain(|name| seen.insert(name.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(file_name: &str, content: &str) -> Manifest {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(file_name);
+        std::fs::write(&path, content).unwrap();
+        Manifest::load(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn tracks_parent_keys_and_lines() {
+        let keys = object_keys("{\n  \"a\": { \"b\": [ { \"c\": \"x\\\"y\" } ] },\n  \"d\": 1\n}");
+        let keys: Vec<(Vec<&str>, &str, usize)> = keys.iter()
+            .map(|(parents, key, line)| (parents.iter().map(String::as_str).collect(), key.as_str(), *line))
+            .collect();
+        assert_eq!(keys, [
+            (vec![], "a", 2),
+            (vec!["a"], "b", 2),
+            (vec!["a", "b", "[]"], "c", 2),
+            (vec![], "d", 3),
+        ]);
+    }
+
+    #[test]
+    fn finds_package_json_dependencies() {
+        let manifest = load("package.json", r#"{
+  "name": "app",
+  "scripts": { "react": "echo" },
+  "dependencies": {
+    "react": "^18.0.0"
+  },
+  "devDependencies": {
+    "@types/node": "^20.0.0",
+    "react": "^18.0.0"
+  }
+}"#);
+        assert_eq!(manifest.dependencies, ["react", "@types/node"]);
+        assert_eq!(manifest.line_of("react"), Some(5));
+        assert_eq!(manifest.line_of("@types/node"), Some(8));
+        assert_eq!(manifest.line_of("app"), None);
+    }
+
+    #[test]
+    fn finds_lockfile_v1_dependencies() {
+        let manifest = load("package-lock.json", r#"{
+  "lockfileVersion": 1,
+  "dependencies": {
+    "a": {
+      "version": "1.0.0",
+      "requires": { "c": "^1.0.0" },
+      "dependencies": {
+        "b": { "version": "2.0.0" }
+      }
+    },
+    "c": { "version": "1.0.0" }
+  }
+}"#);
+        assert_eq!(manifest.dependencies, ["a", "b", "c"]);
+        assert_eq!(manifest.line_of("a"), Some(4));
+        assert_eq!(manifest.line_of("b"), Some(8));
+        assert_eq!(manifest.line_of("c"), Some(11));
+    }
+
+    #[test]
+    fn finds_lockfile_v2_and_v3_packages() {
+        for version in [2, 3] {
+            let v1_section = if version == 2 {
+                r#",
+  "dependencies": { "stale": { "version": "0.1.0" } }"#
+            } else {
+                ""
+            };
+            let manifest = load("npm-shrinkwrap.json", &format!(r#"{{
+  "lockfileVersion": {},
+  "packages": {{
+    "": {{ "dependencies": {{ "a": "^1.0.0" }} }},
+    "node_modules/a": {{ "version": "1.0.0" }},
+    "node_modules/a/node_modules/@scope/b": {{ "version": "2.0.0" }},
+    "node_modules/@scope/b": {{ "version": "2.1.0" }}
+  }}{}
+}}"#, version, v1_section));
+            assert_eq!(manifest.dependencies, ["a", "@scope/b"], "v{}", version);
+            assert_eq!(manifest.line_of("a"), Some(5), "v{}", version);
+            assert_eq!(manifest.line_of("@scope/b"), Some(6), "v{}", version);
+            assert_eq!(manifest.line_of("stale"), None, "v{}", version);
+        }
+    }
+}
@@ -40,7 +40,8 @@ impl NpmClient {
         Ok(package_info)
     }
 
-    pub async fn get_download_stats(&self, package_name: &str, period: &str) -> Result<NpmDownloadStats> {
+    /// `None` when the downloads API has no data or returns an error status.
+    pub async fn get_download_stats(&self, package_name: &str, period: &str) -> Result<Option<NpmDownloadStats>> {
         let url = format!("https://api.npmjs.org/downloads/point/{}/{}", period, package_name);
         
         let response = self
@@ -51,12 +52,7 @@ impl NpmClient {
             .context("Failed to fetch download stats")?;
 
         if !response.status().is_success() {
-            return Ok(NpmDownloadStats {
-                downloads: 0,
-                start: "".to_string(),
-                end: "".to_string(),
-                package: package_name.to_string(),
-            });
+            return Ok(None);
         }
 
         let stats: NpmDownloadStats = response
@@ -64,25 +60,24 @@ impl NpmClient {
             .await
             .context("Failed to parse download stats JSON")?;
 
-        Ok(stats)
+        Ok(Some(stats))
     }
 
-    pub async fn get_weekly_downloads(&self, package_name: &str) -> Result<u64> {
+    pub async fn get_weekly_downloads(&self, package_name: &str) -> Result<Option<u64>> {
         let stats = self.get_download_stats(package_name, "last-week").await?;
-        Ok(stats.downloads)
+        Ok(stats.map(|s| s.downloads))
     }
 
-    pub async fn get_monthly_downloads(&self, package_name: &str) -> Result<u64> {
+    pub async fn get_monthly_downloads(&self, package_name: &str) -> Result<Option<u64>> {
         let stats = self.get_download_stats(package_name, "last-month").await?;
-        Ok(stats.downloads)
+        Ok(stats.map(|s| s.downloads))
     }
 
-    pub fn get_latest_version_info(&self, package_info: &NpmPackageInfo) -> Option<(String, DateTime<Utc>)> {
-        // Get the latest version from dist-tags
-        let latest_version = package_info.dist_tags.get("latest")?;
-        let publish_date = package_info.time.get(latest_version)?.clone();
+    pub fn get_tag_version_info(&self, package_info: &NpmPackageInfo, tag: &str) -> Option<(String, DateTime<Utc>)> {
+        let version = package_info.dist_tags.get(tag)?;
+        let publish_date = *package_info.time.get(version)?;
         
-        Some((latest_version.clone(), publish_date))
+        Some((version.clone(), publish_date))
     }
 
 }
\ No newline at end of file
@@ -1,13 +1,47 @@
 use anyhow::{Context, Result};
+use schemars::generate::SchemaSettings;
 use chrono::{DateTime, Utc};
-use serde_json::{json, Value};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Write;
 
+use crate::check::CheckResult;
 use crate::config::Config;
+use crate::diff::{Delta, FieldChange, ReportDiff};
 use crate::scoring::PackageScorer;
-use crate::types::{NpmPackageInfo, PackageReport};
+use crate::history::HistoryData;
+use crate::manifest::Manifest;
+use crate::fsutil::write_atomic;
+use crate::fields::{compare_values, field_names, get_field_value, report_value};
+use crate::types::{
+    DistTag, Maintainer, NpmPackageInfo, PackageReport, ReportEnvelope, Thresholds, Tool, REPORT_SCHEMA_VERSION,
+};
+use crate::version::{VersionChange, VersionChangeKind};
+
+mod delimited;
+mod html;
+mod junit;
+mod markdown;
+mod openmetrics;
+mod sarif;
+mod table;
+mod template;
+mod terminal;
+
+use delimited::print_delimited;
+use html::print_html;
+use junit::print_junit;
+use markdown::{print_markdown, print_markdown_check_results};
+use openmetrics::render_openmetrics;
+use sarif::print_sarif;
+use table::print_table;
+use template::print_template;
+use terminal::Terminal;
+
+pub use template::compile as compile_template;
 
 pub fn create_package_report(
-    config: &Config,
+    thresholds: &Thresholds,
     package_info: &NpmPackageInfo,
     latest_version: &str,
     last_publish_date: &DateTime<Utc>,
@@ -15,24 +49,61 @@ pub fn create_package_report(
     monthly_downloads: u64,
 ) -> PackageReport {
     let total_versions = package_info.versions.len();
-    let maintainers_count = package_info.maintainers.as_ref()
-        .and_then(|m| {
+    let maintainers = package_info.maintainers.as_ref()
+        .map(|m| {
             if let Some(array) = m.as_array() {
-                Some(array.len())
+                array.iter().map(parse_maintainer).collect()
             } else {
-                Some(1) // If it's not an array, assume it's a single maintainer
+                vec![parse_maintainer(m)] // If it's not an array, assume it's a single maintainer
             }
         })
-        .unwrap_or(0);
+        .unwrap_or_default();
+    let maintainers_count = maintainers.len();
     
-    let has_recent_activity = PackageScorer::has_recent_activity(last_publish_date, config.max_days);
+    let dist_tags: BTreeMap<String, DistTag> = package_info.dist_tags.iter()
+        .map(|(tag, version)| {
+            let dist_tag = DistTag {
+                version: version.clone(),
+                published: package_info.time.get(version).copied(),
+            };
+            (tag.clone(), dist_tag)
+        })
+        .collect();
+
+    // Activity is judged on the configured channel, e.g. `next` for pre-release-only projects
+    let activity_date = dist_tags.get(&thresholds.tag)
+        .and_then(|t| t.published)
+        .unwrap_or(*last_publish_date);
+
+    let has_recent_activity = PackageScorer::has_recent_activity(&activity_date, thresholds.max_days);
     let package_alive = PackageScorer::is_package_alive(
-        last_publish_date,
+        &activity_date,
+        total_versions,
+        maintainers_count,
+        weekly_downloads,
+        monthly_downloads,
+        thresholds,
+    );
+    let health_score = PackageScorer::health_score(
+        &activity_date,
         total_versions,
         maintainers_count,
         weekly_downloads,
         monthly_downloads,
-        config,
+        thresholds,
+    );
+    let deprecated = package_info.versions.get(latest_version)
+        .and_then(|v| v.get("deprecated"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let reasons = PackageScorer::reasons(
+        deprecated.as_deref(),
+        &activity_date,
+        total_versions,
+        maintainers_count,
+        weekly_downloads,
+        monthly_downloads,
+        thresholds,
     );
 
     PackageReport {
@@ -40,11 +111,16 @@ pub fn create_package_report(
         latest_version: latest_version.to_string(),
         total_versions,
         last_publish_date: *last_publish_date,
+        dist_tags: Some(dist_tags),
         downloads_last_week: weekly_downloads,
         downloads_last_month: monthly_downloads,
         maintainers_count,
+        maintainers,
         has_recent_activity,
         package_alive,
+        health_score,
+        deprecated,
+        reasons,
         description: package_info.description.clone(),
         homepage: package_info.homepage.clone(),
         repository_url: package_info.repository.as_ref()
@@ -56,103 +132,522 @@ pub fn create_package_report(
     }
 }
 
-pub fn print_output(config: &Config, report: &PackageReport) -> Result<()> {
+// Registry entries are `{ "name", "email" }` objects, older ones "Name <email>" strings
+fn parse_maintainer(value: &Value) -> Maintainer {
+    match value {
+        Value::Object(map) => Maintainer {
+            name: map.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+            email: map.get("email").and_then(Value::as_str).map(str::to_string),
+        },
+        Value::String(s) => match s.split_once('<') {
+            Some((name, email)) => Maintainer {
+                name: name.trim().to_string(),
+                email: Some(email.trim_end_matches('>').trim().to_string()),
+            },
+            None => Maintainer { name: s.trim().to_string(), email: None },
+        },
+        _ => Maintainer::default(),
+    }
+}
+
+/// What a run learned about a package besides its report.
+#[derive(Debug, Clone, Default)]
+pub struct PackageContext {
+    /// History as it was before this run was recorded.
+    pub history: HistoryData,
+    /// Every published version, oldest first.
+    pub published_versions: Vec<String>,
+}
+
+/// `--sort FIELD` (ascending) or `--sort -FIELD` (descending). Reports without
+/// the field go last either way; equal values keep the order of the arguments.
+pub fn sort_reports(reports: &mut Vec<PackageReport>, contexts: &mut Vec<PackageContext>, sort: &str) {
+    let (field, descending) = match sort.strip_prefix('-') {
+        Some(field) => (field, true),
+        None => (sort, false),
+    };
+
+    let mut pairs: Vec<(PackageReport, PackageContext)> = reports.drain(..).zip(contexts.drain(..)).collect();
+    pairs.sort_by(|(a, _), (b, _)| {
+        match (get_field_value(a, field).filter(|v| !v.is_null()), get_field_value(b, field).filter(|v| !v.is_null())) {
+            (Some(a), Some(b)) if descending => compare_values(&b, &a),
+            (Some(a), Some(b)) => compare_values(&a, &b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    });
+    (*reports, *contexts) = pairs.into_iter().unzip();
+}
+
+/// `contexts` belong to the reports in the same order.
+/// `check_results` are only rendered by formats that embed them in one document.
+pub fn print_output(
+    config: &Config,
+    reports: &[PackageReport],
+    contexts: &[PackageContext],
+    check_results: &[CheckResult],
+) -> Result<()> {
     let default_format = "default".to_string();
     let format = config.format.as_ref().unwrap_or(&default_format);
 
     match format.as_str() {
-        "json" => print_json_output(report),
-        "default" => print_default_output(report),
+        "csv" => return print_delimited(reports, &config.fields, b','),
+        "tsv" => return print_delimited(reports, &config.fields, b'\t'),
+        "markdown" => return print_markdown(reports),
+        "html" => return print_html(reports, contexts, check_results),
+        "junit" => return print_junit(reports, check_results),
+        "openmetrics" => {
+            let text = render_openmetrics(reports, check_results)?;
+            return match &config.output {
+                // Atomic, so a collector never scrapes a half-written file
+                Some(path) => write_atomic(path, text.as_bytes())
+                    .with_context(|| format!("Failed to write metrics to: {}", path)),
+                None => {
+                    print!("{}", text);
+                    Ok(())
+                }
+            };
+        }
+        "sarif" => {
+            let path = config.manifest.as_deref().context("--format sarif requires --manifest")?;
+            return print_sarif(reports, check_results, &Manifest::load(path)?);
+        }
+        "template" => {
+            let path = config.template.as_deref().context("--format template requires --template")?;
+            let source = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read template: {}", path))?;
+            return print_template(&source, reports, contexts, check_results, config.since);
+        }
+        // Already printed by `print_ndjson` as each package finished
+        "ndjson" => return Ok(()),
+        inline if inline.starts_with("template:") => {
+            return print_template(&inline[9..], reports, contexts, check_results, config.since);
+        }
+        _ => {}
+    }
+
+    if !config.fields.is_empty() {
+        return print_selected_fields(config, format, reports, check_results);
+    }
+
+    match format.as_str() {
+        "json" | "yaml" => print_envelope(config, format, reports, check_results),
+        "table" => print_table(reports, &Terminal::detect()),
+        "default" => {
+            let terminal = Terminal::detect();
+            for (index, report) in reports.iter().enumerate() {
+                if index > 0 {
+                    println!();
+                }
+                print_default_output(report, &terminal)?;
+            }
+            Ok(())
+        }
         field if field.starts_with("field:") => {
             let field_name = &field[6..]; // Remove "field:" prefix
-            print_field_output(report, field_name)
+            reports.iter().try_for_each(|report| print_field_output(report, field_name))
         }
         _ => anyhow::bail!("Invalid output format: {}", format),
     }
 }
 
-fn print_json_output(report: &PackageReport) -> Result<()> {
-    let json_output = serde_json::to_string_pretty(report)
-        .context("Failed to serialize report to JSON")?;
+fn print_envelope(config: &Config, format: &str, reports: &[PackageReport], check_results: &[CheckResult]) -> Result<()> {
+    let thresholds = config.thresholds();
+    let package_thresholds = reports.iter()
+        .map(|report| (report.name.clone(), config.thresholds_for(&report.name)))
+        .filter(|(_, package)| *package != thresholds)
+        .collect();
+
+    // `--fields` replaces the full reports by projections, which the schema describes separately
+    let (reports, fields, projections) = if config.fields.is_empty() {
+        (reports.to_vec(), None, None)
+    } else {
+        let projections = reports.iter()
+            .map(|report| {
+                config.fields.iter()
+                    .map(|field| Ok((field.clone(), lookup_field(report, field)?)))
+                    .collect::<Result<_>>()
+            })
+            .collect::<Result<_>>()?;
+        (Vec::new(), Some(config.fields.clone()), Some(projections))
+    };
+
+    let envelope = ReportEnvelope {
+        schema_version: REPORT_SCHEMA_VERSION,
+        tool: Tool {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        generated_at: Utc::now(),
+        thresholds,
+        package_thresholds,
+        reports,
+        fields,
+        projections,
+        checks: check_results.to_vec(),
+    };
+
+    if format == "yaml" {
+        let yaml_output = serde_yaml::to_string(&envelope).context("Failed to serialize report to YAML")?;
+        print!("{}", yaml_output);
+    } else {
+        let json_output = serde_json::to_string_pretty(&envelope).context("Failed to serialize report to JSON")?;
+        println!("{}", json_output);
+    }
+    Ok(())
+}
+
+/// The `schema` subcommand: JSON Schema of the `--format json` document.
+pub fn print_schema() -> Result<()> {
+    let generator = SchemaSettings::draft2020_12().for_serialize().into_generator();
+    let schema = generator.into_root_schema_for::<ReportEnvelope>();
+    let json_output = serde_json::to_string_pretty(&schema).context("Failed to serialize JSON Schema")?;
     println!("{}", json_output);
     Ok(())
 }
 
-fn print_default_output(report: &PackageReport) -> Result<()> {
-    println!("NPM Package: {}", report.name);
-    println!("Latest Version: {}", report.latest_version);
-    println!("Last Published: {}", report.last_publish_date.format("%Y-%m-%d %H:%M:%S UTC"));
-    println!("Total Versions: {}", report.total_versions);
-    println!("Downloads (Week): {}", format_number(report.downloads_last_week));
-    println!("Downloads (Month): {}", format_number(report.downloads_last_month));
-    println!("Maintainers: {}", report.maintainers_count);
-    println!("Recent Activity: {}", if report.has_recent_activity { "✅ Yes" } else { "❌ No" });
-    println!("Package Status: {}", if report.package_alive { "✅ ACTIVE" } else { "❌ INACTIVE" });
-    
+/// `--format ndjson`: one compact line per report, printed as soon as the
+/// package has been checked, followed by its check results. Each line starts
+/// with `"type": "report"` or `"type": "check"` to tell them apart.
+pub fn print_ndjson(config: &Config, report: &PackageReport, check_results: &[CheckResult]) -> Result<()> {
+    let mut values = serde_json::Map::new();
+    values.insert("type".to_string(), Value::from("report"));
+    if config.fields.is_empty() {
+        if let Value::Object(fields) = report_value(report) {
+            values.extend(fields);
+        }
+    } else {
+        for field in &config.fields {
+            values.insert(field.clone(), lookup_field(report, field)?);
+        }
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let line = serde_json::to_string(&values).context("Failed to serialize report to JSON")?;
+    writeln!(stdout, "{}", line).context("Failed to write output")?;
+    for result in check_results {
+        let mut values = serde_json::Map::new();
+        values.insert("type".to_string(), Value::from("check"));
+        if let Value::Object(fields) = serde_json::to_value(result).context("Failed to serialize check result to JSON")? {
+            values.extend(fields);
+        }
+        let line = serde_json::to_string(&values).context("Failed to serialize check result to JSON")?;
+        writeln!(stdout, "{}", line).context("Failed to write output")?;
+    }
+    stdout.flush().context("Failed to write output")
+}
+
+fn print_default_output(report: &PackageReport, terminal: &Terminal) -> Result<()> {
+    for (label, value) in default_lines(report, terminal.color) {
+        println!("{}: {}", label, value);
+    }
+    Ok(())
+}
+
+/// The labelled lines of the default output, shared with the markdown details.
+/// Without `emoji` the verdicts are plain words, for logs and `NO_COLOR`.
+fn default_lines(report: &PackageReport, emoji: bool) -> Vec<(&'static str, String)> {
+    let mark = |ok: bool, text: &str| match (emoji, ok) {
+        (true, true) => format!("✅ {}", text),
+        (true, false) => format!("❌ {}", text),
+        (false, _) => text.to_string(),
+    };
+
+    let mut lines = vec![
+        ("NPM Package", report.name.clone()),
+        ("Latest Version", report.latest_version.clone()),
+        ("Last Published", report.last_publish_date.format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+    ];
+    if let Some(dist_tags) = report.dist_tags.as_ref().filter(|dist_tags| dist_tags.len() > 1) {
+        let tags: Vec<String> = dist_tags.iter()
+            .map(|(tag, dist_tag)| format!("{}={}", tag, dist_tag.version))
+            .collect();
+        lines.push(("Dist Tags", tags.join(", ")));
+    }
+    lines.push(("Total Versions", report.total_versions.to_string()));
+    lines.push(("Downloads (Week)", format_number(report.downloads_last_week)));
+    lines.push(("Downloads (Month)", format_number(report.downloads_last_month)));
+    lines.push(("Maintainers", report.maintainers_count.to_string()));
+    lines.push(("Recent Activity", mark(report.has_recent_activity, if report.has_recent_activity { "Yes" } else { "No" })));
+    lines.push(("Package Status", mark(report.package_alive, if report.package_alive { "ACTIVE" } else { "INACTIVE" })));
+    lines.push(("Health Score", format!("{}/100", report.health_score)));
+
+    if !report.reasons.is_empty() {
+        let messages: Vec<&str> = report.reasons.iter().map(|r| r.message.as_str()).collect();
+        lines.push(("Reasons", messages.join("; ")));
+    }
+
     if let Some(description) = &report.description {
-        println!("Description: {}", description);
+        lines.push(("Description", description.clone()));
     }
-    
+
     if let Some(homepage) = &report.homepage {
-        println!("Homepage: {}", homepage);
+        lines.push(("Homepage", homepage.clone()));
     }
-    
+
     if let Some(repo_url) = &report.repository_url {
-        println!("Repository: {}", repo_url);
+        lines.push(("Repository", repo_url.clone()));
     }
-    
+
     if let Some(license) = &report.license {
-        println!("License: {}", license);
+        lines.push(("License", license.clone()));
     }
-    
+
     if let Some(keywords) = &report.keywords {
         if !keywords.is_empty() {
-            println!("Keywords: {}", keywords.join(", "));
+            lines.push(("Keywords", keywords.join(", ")));
         }
     }
 
-    Ok(())
+    lines
 }
 
 fn print_field_output(report: &PackageReport, field_name: &str) -> Result<()> {
-    let value = get_field_value(report, field_name)
-        .with_context(|| format!("Field '{}' not found", field_name))?;
+    let value = lookup_field(report, field_name)?;
     
+    println!("{}", display_value(&value));
+    
+    Ok(())
+}
+
+/// `--fields`: projections in the envelope with `--format json` or `yaml`, a tab-separated line per report otherwise.
+fn print_selected_fields(
+    config: &Config,
+    format: &str,
+    reports: &[PackageReport],
+    check_results: &[CheckResult],
+) -> Result<()> {
+    if matches!(format, "json" | "yaml") {
+        return print_envelope(config, format, reports, check_results);
+    }
+
+    let rows = reports.iter()
+        .map(|report| config.fields.iter().map(|field| lookup_field(report, field)).collect::<Result<Vec<_>>>())
+        .collect::<Result<Vec<_>>>()?;
+
+    for values in rows {
+        let line: Vec<String> = values.iter().map(display_value).collect();
+        println!("{}", line.join("\t"));
+    }
+
+    Ok(())
+}
+
+fn lookup_field(report: &PackageReport, field_name: &str) -> Result<Value> {
+    get_field_value(report, field_name).with_context(|| {
+        format!(
+            "Field '{}' not found. Available fields: {}",
+            field_name,
+            field_names(report).join(", ")
+        )
+    })
+}
+
+fn display_value(value: &Value) -> String {
     match value {
-        Value::String(s) => println!("{}", s),
-        Value::Number(n) => println!("{}", n),
-        Value::Bool(b) => println!("{}", b),
+        Value::String(s) => match DateTime::parse_from_rfc3339(s) {
+            Ok(date) => date.with_timezone(&Utc).format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            Err(_) => s.clone(),
+        },
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
         Value::Array(arr) => {
-            let strings: Vec<String> = arr.iter()
-                .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                .collect();
-            println!("{}", strings.join(", "));
+            let strings: Vec<String> = arr.iter().map(display_value).collect();
+            strings.join(", ")
+        }
+        Value::Null => "null".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// One machine-readable line per check: JSON with `--format json`, logfmt otherwise.
+/// With `--format markdown` the checks become a table, so the output stays pasteable.
+/// JSON and YAML reports carry them in the envelope, so they are only printed
+/// here next to `--diff`, which replaces the reports.
+pub fn print_check_results(config: &Config, results: &[CheckResult]) -> Result<()> {
+    if config.format.as_deref() == Some("markdown") {
+        print_markdown_check_results(results);
+        return Ok(());
+    }
+    // Already part of the HTML, JUnit, SARIF or OpenMetrics document, streamed with NDJSON,
+    // or left out of CSV/TSV, where extra lines would break the table
+    if matches!(config.format.as_deref(), Some("html" | "junit" | "sarif" | "openmetrics" | "ndjson" | "csv" | "tsv")) {
+        return Ok(());
+    }
+    if matches!(config.format.as_deref(), Some("json" | "yaml")) && config.diff.is_none() {
+        return Ok(());
+    }
+    if config.format.as_deref() == Some("yaml") {
+        if !results.is_empty() {
+            let yaml_output = serde_yaml::to_string(results).context("Failed to serialize check results to YAML")?;
+            print!("---\n{}", yaml_output);
+        }
+        return Ok(());
+    }
+
+    for result in results {
+        if config.format.as_deref() == Some("json") {
+            let json_output = serde_json::to_string(result)
+                .context("Failed to serialize check result to JSON")?;
+            println!("{}", json_output);
+        } else {
+            let mut line = format!(
+                "package={} check={} field={} triggered={} magnitude={}",
+                result.package, result.check, result.field, result.triggered, result.magnitude
+            );
+            if let Some(version) = &result.version_change {
+                line.push_str(&format!(
+                    " version_change={} versions_skipped={}",
+                    version.kind.as_str(), version.versions_skipped
+                ));
+            }
+            if let Some(error) = &result.error {
+                line.push_str(&format!(" error={:?}", error));
+            }
+            println!("{}", line);
         }
-        Value::Null => println!("null"),
-        _ => println!("{}", value),
     }
-    
     Ok(())
 }
 
-fn get_field_value(report: &PackageReport, field_name: &str) -> Option<Value> {
-    match field_name {
-        "name" => Some(json!(report.name)),
-        "latest_version" => Some(json!(report.latest_version)),
-        "total_versions" => Some(json!(report.total_versions)),
-        "last_publish_date" => Some(json!(report.last_publish_date.format("%Y-%m-%d %H:%M:%S UTC").to_string())),
-        "downloads_last_week" => Some(json!(report.downloads_last_week)),
-        "downloads_last_month" => Some(json!(report.downloads_last_month)),
-        "maintainers_count" => Some(json!(report.maintainers_count)),
-        "has_recent_activity" => Some(json!(report.has_recent_activity)),
-        "package_alive" => Some(json!(report.package_alive)),
-        "description" => Some(json!(report.description)),
-        "homepage" => Some(json!(report.homepage)),
-        "repository_url" => Some(json!(report.repository_url)),
-        "license" => Some(json!(report.license)),
-        "keywords" => Some(json!(report.keywords)),
-        _ => None,
+/// `--diff` of every package against its baseline. JSON is a single array so
+/// that several packages still make one document.
+pub fn print_diffs(format: &str, reports: &[PackageReport], contexts: &[PackageContext], since: Option<i64>) -> Result<()> {
+    let diffs: Vec<ReportDiff> = reports.iter()
+        .zip(contexts)
+        .map(|(report, context)| {
+            let baseline = context.history.baseline(since).map(|b| (&b.report, b.recorded_at));
+            ReportDiff::between(baseline, report, &context.published_versions)
+        })
+        .collect();
+
+    match format {
+        "json" => {
+            let json_output = serde_json::to_string_pretty(&diffs)
+                .context("Failed to serialize diff to JSON")?;
+            println!("{}", json_output);
+        }
+        "markdown" => diffs.iter().for_each(print_markdown_diff),
+        "default" => diffs.iter().for_each(print_default_diff),
+        _ => anyhow::bail!("Invalid diff format: {}", format),
     }
+    Ok(())
+}
+
+fn print_default_diff(diff: &ReportDiff) {
+    let Some(recorded_at) = diff.baseline_recorded_at else {
+        println!("No history to compare {} against", diff.package);
+        return;
+    };
+    let since = recorded_at.format("%Y-%m-%d %H:%M:%S UTC");
+
+    if diff.changes.is_empty() {
+        println!("No changes in {} since {}", diff.package, since);
+        return;
+    }
+
+    println!("Changes in {} since {}:", diff.package, since);
+    for change in &diff.changes {
+        match &change.delta {
+            Delta::List { .. } => println!("  {}: {}", change.field, describe_delta(change)),
+            _ => println!(
+                "  {}: {} -> {}{}",
+                change.field,
+                display_value(&change.old),
+                display_value(&change.new),
+                match describe_delta(change) {
+                    d if d.is_empty() => d,
+                    d => format!(" ({})", d),
+                }
+            ),
+        }
+    }
+}
+
+fn print_markdown_diff(diff: &ReportDiff) {
+    let Some(recorded_at) = diff.baseline_recorded_at else {
+        println!("No history to compare `{}` against.", diff.package);
+        return;
+    };
+    let since = recorded_at.format("%Y-%m-%d %H:%M:%S UTC");
+
+    println!("### Changes in `{}` since {}", diff.package, since);
+    println!();
+
+    if diff.changes.is_empty() {
+        println!("No changes.");
+        return;
+    }
+
+    println!("| Field | Old | New | Change |");
+    println!("|-------|-----|-----|--------|");
+    for change in &diff.changes {
+        println!(
+            "| `{}` | {} | {} | {} |",
+            change.field,
+            escape_markdown_cell(&display_value(&change.old)),
+            escape_markdown_cell(&display_value(&change.new)),
+            escape_markdown_cell(&describe_delta(change)),
+        );
+    }
+}
+
+fn describe_delta(change: &FieldChange) -> String {
+    match &change.delta {
+        Delta::Number { change, percent, .. } => match percent {
+            Some(percent) => format!("{:+}, {:+.1}%", change, percent),
+            None => format!("{:+}", change),
+        },
+        Delta::List { added, removed } => {
+            let mut parts = Vec::new();
+            if !added.is_empty() {
+                parts.push(format!("added: {}", display_value(&Value::Array(added.clone()))));
+            }
+            if !removed.is_empty() {
+                parts.push(format!("removed: {}", display_value(&Value::Array(removed.clone()))));
+            }
+            parts.join("; ")
+        }
+        Delta::Duration { seconds, days } => {
+            if *days != 0 {
+                format!("{:+} days", days)
+            } else {
+                format!("{:+} seconds", seconds)
+            }
+        }
+        Delta::Version(version) => describe_version_change(version),
+        Delta::Value => String::new(),
+    }
+}
+
+fn describe_version_change(version: &VersionChange) -> String {
+    let mut description = version.kind.as_str().to_string();
+    if version.versions_skipped > 0 {
+        description.push_str(&format!(", {} versions skipped", version.versions_skipped));
+    }
+    if version.kind == VersionChangeKind::Downgrade {
+        description.push_str(", suspicious");
+    }
+    description
+}
+
+// The note column of check tables: the error, or how the version moved
+fn check_note(result: &CheckResult) -> String {
+    match (&result.error, &result.version_change) {
+        (Some(error), _) => format!("error: {}", error),
+        (None, Some(version)) => describe_version_change(version),
+        (None, None) => String::new(),
+    }
+}
+
+fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+// Registry text such as descriptions must not open tags in HTML or markdown
+fn escape_html(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
 }
 
 fn format_number(num: u64) -> String {
@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use crate::fields::{get_field_value, report_value};
+use crate::types::PackageReport;
+
+/// CSV/TSV: a header row, then one row per report. Values containing the
+/// delimiter, quotes or newlines are quoted, so descriptions survive intact.
+pub fn print_delimited(reports: &[PackageReport], columns: &[String], delimiter: u8) -> Result<()> {
+    let columns = if columns.is_empty() {
+        default_columns(reports)
+    } else {
+        columns.to_vec()
+    };
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(std::io::stdout());
+
+    writer.write_record(&columns).context("Failed to write header row")?;
+
+    for report in reports {
+        let row: Vec<String> = columns.iter()
+            .map(|column| get_field_value(report, column).map(|v| cell(&v)).unwrap_or_default())
+            .collect();
+        writer.write_record(&row)
+            .with_context(|| format!("Failed to write row for: {}", report.name))?;
+    }
+
+    writer.flush().context("Failed to write output")?;
+    Ok(())
+}
+
+// Every top-level field except maps such as dist_tags, which need a path
+fn default_columns(reports: &[PackageReport]) -> Vec<String> {
+    let sample = reports.first().cloned().unwrap_or_default();
+    match report_value(&sample) {
+        Value::Object(map) => map.into_iter()
+            .filter(|(_, value)| !value.is_object())
+            .map(|(key, _)| key)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Dates are already RFC 3339 strings in the serialized report
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(", "),
+        // List entries are shown by name (maintainers) or message (reasons)
+        Value::Object(map) => match map.get("name").or_else(|| map.get("message")) {
+            Some(name) => cell(name),
+            None => value.to_string(),
+        },
+        _ => value.to_string(),
+    }
+}
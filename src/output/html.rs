@@ -0,0 +1,287 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::fmt::Write;
+
+use crate::check::CheckResult;
+use crate::history::HistoryData;
+use crate::types::PackageReport;
+
+use super::{check_note, escape_html, format_number, PackageContext};
+
+const SPARKLINE_WIDTH: f64 = 120.0;
+const SPARKLINE_HEIGHT: f64 = 24.0;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #1f2328; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 1.2rem; margin-top: 2rem; }
+.meta { color: #59636e; margin-top: 0; }
+.filters { margin: 1rem 0; }
+.filters label { margin-right: 1rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #d1d9e0; padding: 0.4rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+#packages th { cursor: pointer; user-select: none; white-space: nowrap; }
+#packages th[aria-sort="ascending"]::after { content: " ▲"; }
+#packages th[aria-sort="descending"]::after { content: " ▼"; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+tr.active .verdict { color: #1a7f37; }
+tr.inactive .verdict { color: #d1242f; }
+.score { display: inline-block; min-width: 2.5rem; padding: 0.1rem 0.4rem; border-radius: 1rem; text-align: center; color: #fff; }
+.score.good { background: #1a7f37; }
+.score.fair { background: #9a6700; }
+.score.poor { background: #d1242f; }
+.spark { color: #0969da; }
+ul.reasons { margin: 0; padding-left: 1.2rem; }
+"#;
+
+const SCRIPT: &str = r#"
+(function () {
+  var table = document.getElementById("packages");
+  var body = table.tBodies[0];
+  var headers = table.tHead.rows[0].cells;
+
+  Array.prototype.forEach.call(headers, function (th, column) {
+    th.addEventListener("click", function () {
+      var ascending = th.getAttribute("aria-sort") !== "ascending";
+      Array.prototype.forEach.call(headers, function (h) { h.removeAttribute("aria-sort"); });
+      th.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+
+      var numeric = th.dataset.type === "number";
+      var rows = Array.prototype.slice.call(body.rows);
+      rows.sort(function (a, b) {
+        var x = a.cells[column].dataset.sort, y = b.cells[column].dataset.sort;
+        var order = numeric ? Number(x) - Number(y) : x.localeCompare(y);
+        return ascending ? order : -order;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+
+  document.querySelectorAll("input[name=verdict]").forEach(function (input) {
+    input.addEventListener("change", function () {
+      Array.prototype.forEach.call(body.rows, function (row) {
+        row.hidden = input.value !== "all" && !row.classList.contains(input.value);
+      });
+    });
+  });
+})();
+"#;
+
+/// A standalone dashboard: styles and scripts are inline, so the file can be
+/// archived or mailed as is. Rows are rendered server-side and the script only
+/// adds sorting and filtering, so the table is complete without JavaScript.
+pub fn print_html(reports: &[PackageReport], contexts: &[PackageContext], check_results: &[CheckResult]) -> Result<()> {
+    let active = reports.iter().filter(|r| r.package_alive).count();
+    let mut html = String::new();
+
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, "<html lang=\"en\">")?;
+    writeln!(html, "<head>")?;
+    writeln!(html, "<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>npm package health</title>")?;
+    writeln!(html, "<style>{}</style>", STYLE)?;
+    writeln!(html, "</head>")?;
+    writeln!(html, "<body>")?;
+    writeln!(html, "<h1>npm package health</h1>")?;
+    writeln!(
+        html,
+        "<p class=\"meta\">Generated {} · {} packages · {} active · {} inactive</p>",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        reports.len(),
+        active,
+        reports.len() - active
+    )?;
+
+    writeln!(html, "<div class=\"filters\">Show:")?;
+    for (value, label) in [("all", "All"), ("active", "Active"), ("inactive", "Inactive")] {
+        writeln!(
+            html,
+            "<label><input type=\"radio\" name=\"verdict\" value=\"{}\"{}> {}</label>",
+            value,
+            if value == "all" { " checked" } else { "" },
+            label
+        )?;
+    }
+    writeln!(html, "</div>")?;
+
+    writeln!(html, "<table id=\"packages\">")?;
+    writeln!(
+        html,
+        "<thead><tr>\
+         <th data-type=\"text\">Package</th>\
+         <th data-type=\"text\">Version</th>\
+         <th data-type=\"text\">Last publish</th>\
+         <th data-type=\"number\">Weekly downloads</th>\
+         <th data-type=\"number\">Downloads trend</th>\
+         <th data-type=\"number\">Maintainers</th>\
+         <th data-type=\"number\">Health</th>\
+         <th data-type=\"text\">Verdict</th>\
+         <th data-type=\"number\">Reasons</th>\
+         </tr></thead>"
+    )?;
+    writeln!(html, "<tbody>")?;
+    for (index, report) in reports.iter().enumerate() {
+        let history = contexts.get(index).map(|c| &c.history);
+        write_row(&mut html, report, history)?;
+    }
+    writeln!(html, "</tbody>")?;
+    writeln!(html, "</table>")?;
+
+    if !check_results.is_empty() {
+        write_checks(&mut html, check_results)?;
+    }
+
+    writeln!(html, "<script>{}</script>", SCRIPT)?;
+    writeln!(html, "</body>")?;
+    writeln!(html, "</html>")?;
+
+    print!("{}", html);
+    Ok(())
+}
+
+fn write_row(html: &mut String, report: &PackageReport, history: Option<&HistoryData>) -> Result<()> {
+    let verdict = if report.package_alive { "active" } else { "inactive" };
+    let name = escape_html(&report.name);
+    let version = escape_html(&report.latest_version);
+
+    // The trend sorts by the change over the recorded history
+    let series = download_series(report, history);
+    let trend = match (series.first(), series.last()) {
+        (Some(first), Some(last)) if series.len() > 1 => *last as i64 - *first as i64,
+        _ => 0,
+    };
+
+    writeln!(html, "<tr class=\"{}\">", verdict)?;
+    // Only web links, a registry entry could carry a `javascript:` URL
+    let homepage = report.homepage.as_ref()
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"));
+    match homepage {
+        Some(homepage) => writeln!(
+            html,
+            "<td data-sort=\"{}\"><a href=\"{}\">{}</a></td>",
+            name, escape_html(homepage), name
+        )?,
+        None => writeln!(html, "<td data-sort=\"{}\">{}</td>", name, name)?,
+    }
+    writeln!(html, "<td data-sort=\"{}\">{}</td>", version, version)?;
+    writeln!(
+        html,
+        "<td data-sort=\"{}\">{}</td>",
+        report.last_publish_date.to_rfc3339(),
+        report.last_publish_date.format("%Y-%m-%d")
+    )?;
+    writeln!(
+        html,
+        "<td class=\"number\" data-sort=\"{}\">{}</td>",
+        report.downloads_last_week,
+        format_number(report.downloads_last_week)
+    )?;
+    writeln!(html, "<td data-sort=\"{}\">{}</td>", trend, sparkline(&series))?;
+    writeln!(
+        html,
+        "<td class=\"number\" data-sort=\"{}\">{}</td>",
+        report.maintainers_count, report.maintainers_count
+    )?;
+    writeln!(
+        html,
+        "<td data-sort=\"{}\"><span class=\"score {}\">{}</span></td>",
+        report.health_score,
+        score_class(report.health_score),
+        report.health_score
+    )?;
+    writeln!(
+        html,
+        "<td class=\"verdict\" data-sort=\"{}\">{}</td>",
+        verdict,
+        if report.package_alive { "Active" } else { "Inactive" }
+    )?;
+    if report.reasons.is_empty() {
+        writeln!(html, "<td data-sort=\"0\">—</td>")?;
+    } else {
+        writeln!(html, "<td data-sort=\"{}\"><ul class=\"reasons\">", report.reasons.len())?;
+        for reason in &report.reasons {
+            writeln!(
+                html,
+                "<li title=\"{}\">{}</li>",
+                escape_html(&reason.rule),
+                escape_html(&reason.message)
+            )?;
+        }
+        writeln!(html, "</ul></td>")?;
+    }
+    writeln!(html, "</tr>")?;
+
+    Ok(())
+}
+
+fn write_checks(html: &mut String, check_results: &[CheckResult]) -> Result<()> {
+    writeln!(html, "<h2>Checks</h2>")?;
+    writeln!(html, "<table>")?;
+    writeln!(html, "<thead><tr><th>Package</th><th>Check</th><th>Triggered</th><th>Magnitude</th><th>Note</th></tr></thead>")?;
+    writeln!(html, "<tbody>")?;
+    for result in check_results {
+        let note = check_note(result);
+        writeln!(
+            html,
+            "<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td class=\"number\">{}</td><td>{}</td></tr>",
+            escape_html(&result.package),
+            escape_html(&result.check),
+            if result.triggered { "yes" } else { "no" },
+            result.magnitude,
+            escape_html(&note)
+        )?;
+    }
+    writeln!(html, "</tbody>")?;
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+// Weekly downloads of every recorded run, oldest first, then the current run
+fn download_series(report: &PackageReport, history: Option<&HistoryData>) -> Vec<u64> {
+    let mut series: Vec<u64> = history
+        .map(|h| h.entries.iter().map(|e| e.report.downloads_last_week).collect())
+        .unwrap_or_default();
+    series.push(report.downloads_last_week);
+    series
+}
+
+fn sparkline(series: &[u64]) -> String {
+    if series.len() < 2 {
+        return "—".to_string();
+    }
+
+    let min = series.iter().copied().min().unwrap_or(0);
+    let max = series.iter().copied().max().unwrap_or(0);
+    let range = (max - min).max(1) as f64;
+    let step = SPARKLINE_WIDTH / (series.len() - 1) as f64;
+
+    // One pixel of padding keeps the stroke inside the box
+    let points: Vec<String> = series.iter().enumerate()
+        .map(|(index, value)| {
+            let x = index as f64 * step;
+            let y = 1.0 + (SPARKLINE_HEIGHT - 2.0) * (1.0 - (value - min) as f64 / range);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+
+    format!(
+        "<svg class=\"spark\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\">\
+         <title>{} runs, {} to {} weekly downloads</title>\
+         <polyline fill=\"none\" stroke=\"currentColor\" stroke-width=\"1.5\" points=\"{}\"/></svg>",
+        series.len(),
+        format_number(min),
+        format_number(max),
+        points.join(" "),
+        w = SPARKLINE_WIDTH,
+        h = SPARKLINE_HEIGHT,
+    )
+}
+
+fn score_class(score: u32) -> &'static str {
+    match score {
+        80.. => "good",
+        50..=79 => "fair",
+        _ => "poor",
+    }
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::fmt::Write;
+
+use crate::check::CheckResult;
+use crate::types::PackageReport;
+
+use super::{escape_html, format_number};
+
+const SUITE_NAME: &str = env!("CARGO_PKG_NAME");
+
+/// JUnit XML: one testcase per package. An inactive or deprecated package, or
+/// one with a triggered check, is a failure whose message lists the reasons;
+/// a check that could not be evaluated is an error.
+pub fn print_junit(reports: &[PackageReport], check_results: &[CheckResult]) -> Result<()> {
+    let cases: Vec<TestCase> = reports.iter()
+        .map(|report| TestCase::new(report, check_results))
+        .collect();
+    let failures = cases.iter().filter(|c| !c.failures.is_empty()).count();
+    let errors = cases.iter().filter(|c| !c.errors.is_empty()).count();
+
+    let mut xml = String::new();
+    writeln!(xml, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">",
+        SUITE_NAME, cases.len(), failures, errors
+    )?;
+    writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"0\" timestamp=\"{}\">",
+        SUITE_NAME,
+        cases.len(),
+        failures,
+        errors,
+        Utc::now().format("%Y-%m-%dT%H:%M:%S")
+    )?;
+
+    for case in &cases {
+        write_case(&mut xml, case)?;
+    }
+
+    writeln!(xml, "  </testsuite>")?;
+    writeln!(xml, "</testsuites>")?;
+
+    print!("{}", xml);
+    Ok(())
+}
+
+struct TestCase<'a> {
+    report: &'a PackageReport,
+    /// `rule: message` lines; the first rule becomes the failure type.
+    failures: Vec<(String, String)>,
+    errors: Vec<String>,
+}
+
+impl<'a> TestCase<'a> {
+    fn new(report: &'a PackageReport, check_results: &[CheckResult]) -> Self {
+        // An active package only fails on deprecation; its other reasons are advisory
+        let mut failures: Vec<(String, String)> = report.reasons.iter()
+            .filter(|r| !report.package_alive || r.rule == "deprecated")
+            .map(|r| (r.rule.clone(), r.message.clone()))
+            .collect();
+        if !report.package_alive {
+            failures.insert(0, ("inactive-package".to_string(), format!("{} is inactive", report.name)));
+        }
+
+        let mut errors = Vec::new();
+        for result in check_results.iter().filter(|r| r.package == report.name) {
+            if let Some(error) = &result.error {
+                errors.push(format!("check '{}': {}", result.check, error));
+            } else if result.triggered {
+                failures.push((
+                    "check-triggered".to_string(),
+                    format!("check '{}' triggered (magnitude {})", result.check, result.magnitude),
+                ));
+            }
+        }
+
+        Self { report, failures, errors }
+    }
+}
+
+fn write_case(xml: &mut String, case: &TestCase) -> Result<()> {
+    let report = case.report;
+    writeln!(
+        xml,
+        "    <testcase classname=\"{}\" name=\"{}\">",
+        SUITE_NAME,
+        escape_html(&report.name)
+    )?;
+
+    if let Some((rule, _)) = case.failures.first() {
+        let messages: Vec<&str> = case.failures.iter().map(|(_, message)| message.as_str()).collect();
+        let details: Vec<String> = case.failures.iter()
+            .map(|(rule, message)| format!("{}: {}", rule, message))
+            .collect();
+        writeln!(
+            xml,
+            "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+            escape_html(rule),
+            escape_html(&messages.join("; ")),
+            escape_html(&details.join("\n"))
+        )?;
+    }
+
+    if !case.errors.is_empty() {
+        writeln!(
+            xml,
+            "      <error type=\"check-error\" message=\"{}\">{}</error>",
+            escape_html(&case.errors.join("; ")),
+            escape_html(&case.errors.join("\n"))
+        )?;
+    }
+
+    writeln!(
+        xml,
+        "      <system-out>version {}, last published {}, {} weekly downloads, {} maintainers, health score {}</system-out>",
+        escape_html(&report.latest_version),
+        report.last_publish_date.format("%Y-%m-%d"),
+        format_number(report.downloads_last_week),
+        report.maintainers_count,
+        report.health_score
+    )?;
+    writeln!(xml, "    </testcase>")?;
+    Ok(())
+}
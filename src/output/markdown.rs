@@ -0,0 +1,72 @@
+use anyhow::Result;
+
+use crate::check::CheckResult;
+use crate::types::PackageReport;
+
+use super::{check_note, default_lines, escape_html, escape_markdown_cell, format_number};
+
+/// GitHub-flavoured markdown for PR comments: a summary table with one row per
+/// package, then the full default output of each package in a `<details>` block.
+pub fn print_markdown(reports: &[PackageReport]) -> Result<()> {
+    println!("| Package | Version | Last publish | Weekly downloads | Verdict | Reasons |");
+    println!("|---------|---------|--------------|------------------|---------|---------|");
+    for report in reports {
+        let reasons: Vec<String> = report.reasons.iter()
+            .map(|reason| escape_markdown_cell(&escape_html(&reason.message)))
+            .collect();
+        println!(
+            "| `{}` | {} | {} | {} | {} | {} |",
+            report.name,
+            escape_markdown_cell(&report.latest_version),
+            report.last_publish_date.format("%Y-%m-%d"),
+            format_number(report.downloads_last_week),
+            verdict(report),
+            if reasons.is_empty() { "—".to_string() } else { reasons.join("<br>") },
+        );
+    }
+
+    for report in reports {
+        println!();
+        println!("<details>");
+        println!(
+            "<summary><code>{}</code> {} — {}</summary>",
+            escape_html(&report.name),
+            escape_html(&report.latest_version),
+            verdict(report)
+        );
+        println!();
+        // The package name is already in the summary line
+        for (label, value) in default_lines(report, true).into_iter().skip(1) {
+            println!("- **{}:** {}", label, escape_html(&value));
+        }
+        println!();
+        println!("</details>");
+    }
+
+    Ok(())
+}
+
+pub fn print_markdown_check_results(results: &[CheckResult]) {
+    if results.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("| Package | Check | Triggered | Magnitude | Note |");
+    println!("|---------|-------|-----------|-----------|------|");
+    for result in results {
+        let note = check_note(result);
+        println!(
+            "| `{}` | `{}` | {} | {} | {} |",
+            result.package,
+            escape_markdown_cell(&result.check),
+            if result.triggered { "⚠️ yes" } else { "no" },
+            result.magnitude,
+            escape_markdown_cell(&escape_html(&note)),
+        );
+    }
+}
+
+fn verdict(report: &PackageReport) -> &'static str {
+    if report.package_alive { "✅ Active" } else { "❌ Inactive" }
+}
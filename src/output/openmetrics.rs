@@ -0,0 +1,69 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::fmt::Write;
+
+use crate::check::CheckResult;
+use crate::types::PackageReport;
+
+type Sample = fn(&PackageReport) -> i64;
+
+const GAUGES: &[(&str, &str, Sample)] = &[
+    ("npm_package_downloads_weekly", "Downloads in the last week.", |r| r.downloads_last_week as i64),
+    ("npm_package_downloads_monthly", "Downloads in the last month.", |r| r.downloads_last_month as i64),
+    ("npm_package_days_since_publish", "Days since the latest version was published.", |r| {
+        (Utc::now() - r.last_publish_date).num_days()
+    }),
+    ("npm_package_versions", "Number of published versions.", |r| r.total_versions as i64),
+    ("npm_package_maintainers", "Number of maintainers.", |r| r.maintainers_count as i64),
+    ("npm_package_alive", "1 if the package meets the liveness criteria.", |r| r.package_alive as i64),
+    ("npm_package_deprecated", "1 if the latest version is deprecated.", |r| r.deprecated.is_some() as i64),
+    ("npm_package_health_score", "Health score from 0 to 100.", |r| r.health_score as i64),
+];
+
+/// OpenMetrics text exposition, one gauge family per metric with a sample per
+/// package, suitable for the node_exporter textfile collector. Check results
+/// are exported too, so nothing else needs to be printed next to them.
+pub fn render_openmetrics(reports: &[PackageReport], check_results: &[CheckResult]) -> Result<String> {
+    let mut text = String::new();
+
+    // A gauge rather than the OpenMetrics `info` type, which the textfile collector rejects
+    writeln!(text, "# TYPE npm_package_info gauge")?;
+    writeln!(text, "# HELP npm_package_info Latest version of the package.")?;
+    for report in reports {
+        writeln!(
+            text,
+            "npm_package_info{{package=\"{}\",version=\"{}\"}} 1",
+            escape_label(&report.name),
+            escape_label(&report.latest_version)
+        )?;
+    }
+
+    for (name, help, sample) in GAUGES {
+        writeln!(text, "# TYPE {} gauge", name)?;
+        writeln!(text, "# HELP {} {}", name, help)?;
+        for report in reports {
+            writeln!(text, "{}{{package=\"{}\"}} {}", name, escape_label(&report.name), sample(report))?;
+        }
+    }
+
+    if !check_results.is_empty() {
+        writeln!(text, "# TYPE npm_package_check_triggered gauge")?;
+        writeln!(text, "# HELP npm_package_check_triggered 1 if the --check expression triggered.")?;
+        for result in check_results {
+            writeln!(
+                text,
+                "npm_package_check_triggered{{package=\"{}\",check=\"{}\"}} {}",
+                escape_label(&result.package),
+                escape_label(&result.check),
+                result.triggered as i64
+            )?;
+        }
+    }
+
+    writeln!(text, "# EOF")?;
+    Ok(text)
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
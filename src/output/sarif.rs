@@ -0,0 +1,93 @@
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+use crate::check::CheckResult;
+use crate::manifest::Manifest;
+use crate::scoring::RULES;
+use crate::types::PackageReport;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const CHECK_RULE: (&str, &str) = ("check-triggered", "A --check expression triggered");
+
+/// SARIF 2.1.0 for code scanning. An inactive package becomes one error listing
+/// its reasons; an active one gets a warning per criterion it falls short of.
+/// Deprecation is always an error. Results point at the package's line in
+/// `--manifest`.
+pub fn print_sarif(reports: &[PackageReport], check_results: &[CheckResult], manifest: &Manifest) -> Result<()> {
+    let mut results = Vec::new();
+
+    for report in reports {
+        if !report.package_alive {
+            let reasons: Vec<&str> = report.reasons.iter()
+                .filter(|r| r.rule != "deprecated")
+                .map(|r| r.message.as_str())
+                .collect();
+            let message = if reasons.is_empty() {
+                format!("{} is inactive", report.name)
+            } else {
+                format!("{} is inactive: {}", report.name, reasons.join("; "))
+            };
+            results.push(result("inactive-package", "error", &message, &report.name, manifest));
+        }
+
+        // The reasons of an inactive package are already in its error
+        for reason in &report.reasons {
+            let deprecated = reason.rule == "deprecated";
+            if report.package_alive || deprecated {
+                let level = if deprecated { "error" } else { "warning" };
+                let message = format!("{}: {}", report.name, reason.message);
+                results.push(result(&reason.rule, level, &message, &report.name, manifest));
+            }
+        }
+    }
+
+    for check in check_results.iter().filter(|c| c.triggered) {
+        let message = format!("{}: check '{}' triggered (magnitude {})", check.package, check.check, check.magnitude);
+        results.push(result(CHECK_RULE.0, "warning", &message, &check.package, manifest));
+    }
+
+    let rules: Vec<Value> = RULES.iter()
+        .chain(std::iter::once(&CHECK_RULE))
+        .map(|(id, description)| json!({
+            "id": id,
+            "shortDescription": { "text": description },
+        }))
+        .collect();
+
+    let sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/jozef-pridavok/npm-activity-check",
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    });
+
+    let json_output = serde_json::to_string_pretty(&sarif)
+        .context("Failed to serialize SARIF report")?;
+    println!("{}", json_output);
+    Ok(())
+}
+
+fn result(rule: &str, level: &str, message: &str, package: &str, manifest: &Manifest) -> Value {
+    let mut physical = json!({
+        "artifactLocation": { "uri": manifest.path.replace('\\', "/") },
+    });
+    if let Some(line) = manifest.line_of(package) {
+        physical["region"] = json!({ "startLine": line });
+    }
+
+    json!({
+        "ruleId": rule,
+        "level": level,
+        "message": { "text": message },
+        "locations": [{ "physicalLocation": physical }],
+    })
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+
+use crate::types::PackageReport;
+
+use super::format_number;
+use super::terminal::{Color, Terminal};
+
+const SEPARATOR: &str = "  ";
+// Below this, the description is left out rather than cut to a few letters
+const MIN_DESCRIPTION_WIDTH: usize = 12;
+
+struct Column {
+    header: &'static str,
+    right_aligned: bool,
+}
+
+const COLUMNS: &[Column] = &[
+    Column { header: "PACKAGE", right_aligned: false },
+    Column { header: "VERSION", right_aligned: false },
+    Column { header: "PUBLISHED", right_aligned: false },
+    Column { header: "WEEKLY", right_aligned: true },
+    Column { header: "MONTHLY", right_aligned: true },
+    Column { header: "MAINTAINERS", right_aligned: true },
+    Column { header: "SCORE", right_aligned: true },
+    Column { header: "STATUS", right_aligned: false },
+];
+
+/// One aligned row per package. The status is green when active, yellow when
+/// active but short of a criterion, red when inactive. The description fills
+/// whatever is left of the terminal width.
+pub fn print_table(reports: &[PackageReport], terminal: &Terminal) -> Result<()> {
+    let rows: Vec<Vec<String>> = reports.iter().map(cells).collect();
+
+    let widths: Vec<usize> = COLUMNS.iter().enumerate()
+        .map(|(index, column)| {
+            rows.iter()
+                .map(|row| row[index].chars().count())
+                .chain(std::iter::once(column.header.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let used: usize = widths.iter().map(|w| w + SEPARATOR.len()).sum();
+    let description_width = match terminal.width {
+        Some(width) => width.saturating_sub(used),
+        None => usize::MAX,
+    };
+    let show_description = description_width >= MIN_DESCRIPTION_WIDTH
+        && reports.iter().any(|r| r.description.is_some());
+
+    let mut header: Vec<String> = COLUMNS.iter().zip(&widths)
+        .map(|(column, width)| pad(column.header, *width, column.right_aligned))
+        .collect();
+    if show_description {
+        header.push("DESCRIPTION".to_string());
+    }
+    println!("{}", header.join(SEPARATOR).trim_end());
+
+    for (report, row) in reports.iter().zip(&rows) {
+        let mut line: Vec<String> = COLUMNS.iter().zip(&widths).zip(row)
+            .map(|((column, width), cell)| pad(cell, *width, column.right_aligned))
+            .collect();
+
+        // Padding is computed on the plain text, escape codes would throw it off
+        let status = line.len() - 1;
+        line[status] = terminal.paint(&line[status], status_color(report));
+
+        if show_description {
+            let description = report.description.as_deref().unwrap_or_default();
+            line.push(truncate(description, description_width));
+        }
+        println!("{}", line.join(SEPARATOR).trim_end());
+    }
+
+    Ok(())
+}
+
+fn cells(report: &PackageReport) -> Vec<String> {
+    vec![
+        report.name.clone(),
+        report.latest_version.clone(),
+        report.last_publish_date.format("%Y-%m-%d").to_string(),
+        format_number(report.downloads_last_week),
+        format_number(report.downloads_last_month),
+        report.maintainers_count.to_string(),
+        report.health_score.to_string(),
+        status_label(report).to_string(),
+    ]
+}
+
+fn status_label(report: &PackageReport) -> &'static str {
+    match status_color(report) {
+        Color::Green => "ACTIVE",
+        Color::Yellow => "AT RISK",
+        Color::Red => "INACTIVE",
+    }
+}
+
+fn status_color(report: &PackageReport) -> Color {
+    if !report.package_alive {
+        Color::Red
+    } else if !report.reasons.is_empty() {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
+
+fn pad(text: &str, width: usize, right_aligned: bool) -> String {
+    if right_aligned {
+        format!("{:>width$}", text, width = width)
+    } else {
+        format!("{:<width$}", text, width = width)
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    // Descriptions are single-line in the table
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= width {
+        return text;
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use minijinja::{Environment, ErrorKind};
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::check::CheckResult;
+use crate::diff::ReportDiff;
+use crate::fields::report_value;
+use crate::types::PackageReport;
+
+use super::{format_number, PackageContext};
+
+const TEMPLATE_NAME: &str = "report";
+
+/// Renders `source` once per package. Besides the report fields the template
+/// sees `verdict`, `versions` (oldest first), `checks` and `diff` against the
+/// history baseline.
+pub fn print_template(
+    source: &str,
+    reports: &[PackageReport],
+    contexts: &[PackageContext],
+    check_results: &[CheckResult],
+    since: Option<i64>,
+) -> Result<()> {
+    let env = environment(source)?;
+    let template = env.get_template(TEMPLATE_NAME)?;
+
+    for (index, report) in reports.iter().enumerate() {
+        let context = contexts.get(index).map(|c| (&c.history, c.published_versions.as_slice()));
+        let versions = context.map(|(_, versions)| versions).unwrap_or_default();
+        let baseline = context
+            .and_then(|(history, _)| history.baseline(since))
+            .map(|b| (&b.report, b.recorded_at));
+        let checks: Vec<&CheckResult> = check_results.iter().filter(|r| r.package == report.name).collect();
+
+        let mut values = report_value(report);
+        if let Value::Object(map) = &mut values {
+            let verdict = if report.package_alive { "active" } else { "inactive" };
+            map.insert("verdict".to_string(), Value::from(verdict));
+            map.insert("versions".to_string(), serde_json::to_value(versions)?);
+            map.insert("checks".to_string(), serde_json::to_value(&checks)?);
+            map.insert("diff".to_string(), serde_json::to_value(ReportDiff::between(baseline, report, versions))?);
+        }
+
+        let output = template.render(&values)
+            .with_context(|| format!("Failed to render template for: {}", report.name))?;
+        if output.ends_with('\n') {
+            print!("{}", output);
+        } else {
+            println!("{}", output);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the template syntax up front, before any package is fetched.
+pub fn compile(source: &str) -> Result<()> {
+    environment(source).map(|_| ())
+}
+
+fn environment(source: &str) -> Result<Environment<'static>> {
+    let mut env = Environment::new();
+    env.add_filter("format_number", format_number);
+    env.add_filter("date", date);
+    env.add_template_owned(TEMPLATE_NAME, source.to_string())
+        .context("Invalid template")?;
+    Ok(env)
+}
+
+// `{{ last_publish_date | date }}`, or with a strftime format: `date("%d %b %Y")`
+fn date(value: String, format: Option<String>) -> Result<String, minijinja::Error> {
+    let date = DateTime::parse_from_rfc3339(&value)
+        .map_err(|e| minijinja::Error::new(ErrorKind::InvalidOperation, format!("Not a date: {}", value)).with_source(e))?;
+    let format = format.as_deref().unwrap_or("%Y-%m-%d");
+
+    // An invalid format only shows up while writing, and `to_string` would panic on it
+    let mut output = String::new();
+    write!(output, "{}", date.with_timezone(&Utc).format(format))
+        .map_err(|_| minijinja::Error::new(ErrorKind::InvalidOperation, format!("Invalid date format: {}", format)))?;
+    Ok(output)
+}
@@ -0,0 +1,47 @@
+use std::io::IsTerminal;
+
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy)]
+pub enum Color {
+    Red,
+    Yellow,
+    Green,
+}
+
+/// What the output may assume about stdout. Colors and emojis are only used
+/// on a terminal and when `NO_COLOR` is unset, so CI logs stay plain text.
+#[derive(Debug, Clone, Copy)]
+pub struct Terminal {
+    pub color: bool,
+    /// Columns available, `None` when not writing to a terminal.
+    pub width: Option<usize>,
+}
+
+impl Terminal {
+    pub fn detect() -> Self {
+        let is_terminal = std::io::stdout().is_terminal();
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+
+        let width = terminal_size::terminal_size()
+            .map(|(terminal_size::Width(width), _)| width as usize)
+            .filter(|_| is_terminal);
+
+        Self {
+            color: is_terminal && !no_color,
+            width,
+        }
+    }
+
+    pub fn paint(&self, text: &str, color: Color) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+        let code = match color {
+            Color::Red => "\x1b[31m",
+            Color::Yellow => "\x1b[33m",
+            Color::Green => "\x1b[32m",
+        };
+        format!("{}{}{}", code, text, RESET)
+    }
+}
@@ -1,8 +1,19 @@
 use chrono::{DateTime, Utc};
-use crate::config::Config;
+use crate::types::{Reason, Thresholds};
 
 pub struct PackageScorer;
 
+/// Every rule ID a finding can carry, with a one-line description for tools
+/// such as SARIF viewers that list the rules separately from the results.
+pub const RULES: &[(&str, &str)] = &[
+    ("inactive-package", "The package does not meet the liveness criteria"),
+    ("deprecated", "The latest version is deprecated on the registry"),
+    ("no-recent-release", "No release within the configured number of days"),
+    ("low-downloads", "Weekly and monthly downloads are below the minimum"),
+    ("few-versions", "Fewer published versions than the minimum"),
+    ("few-maintainers", "Fewer maintainers than the minimum"),
+];
+
 impl PackageScorer {
     pub fn is_package_alive(
         last_publish_date: &DateTime<Utc>,
@@ -10,12 +21,12 @@ impl PackageScorer {
         maintainers_count: usize,
         weekly_downloads: u64,
         monthly_downloads: u64,
-        config: &Config,
+        thresholds: &Thresholds,
     ) -> bool {
-        let has_recent_activity = Self::has_recent_activity(last_publish_date, config.max_days);
-        let has_sufficient_downloads = Self::has_sufficient_downloads(weekly_downloads, monthly_downloads, config);
-        let has_sufficient_versions = total_versions >= config.min_versions;
-        let has_sufficient_maintainers = maintainers_count >= config.min_maintainers;
+        let has_recent_activity = Self::has_recent_activity(last_publish_date, thresholds.max_days);
+        let has_sufficient_downloads = Self::has_sufficient_downloads(weekly_downloads, monthly_downloads, thresholds);
+        let has_sufficient_versions = total_versions >= thresholds.min_versions;
+        let has_sufficient_maintainers = maintainers_count >= thresholds.min_maintainers;
 
         // Package is considered alive if:
         // 1. Has recent activity (published within max_days), OR
@@ -29,8 +40,88 @@ impl PackageScorer {
         *last_publish_date > threshold
     }
 
-    pub fn has_sufficient_downloads(weekly_downloads: u64, monthly_downloads: u64, config: &Config) -> bool {
-        weekly_downloads >= config.min_weekly_downloads || monthly_downloads >= config.min_monthly_downloads
+    pub fn has_sufficient_downloads(weekly_downloads: u64, monthly_downloads: u64, thresholds: &Thresholds) -> bool {
+        weekly_downloads >= thresholds.min_weekly_downloads || monthly_downloads >= thresholds.min_monthly_downloads
+    }
+
+    /// The criteria the package falls short of, in the order `is_package_alive` weighs them,
+    /// after a deprecation notice. An active package can still have reasons, e.g. a single maintainer.
+    pub fn reasons(
+        deprecated: Option<&str>,
+        last_publish_date: &DateTime<Utc>,
+        total_versions: usize,
+        maintainers_count: usize,
+        weekly_downloads: u64,
+        monthly_downloads: u64,
+        thresholds: &Thresholds,
+    ) -> Vec<Reason> {
+        let mut reasons = Vec::new();
+
+        if let Some(message) = deprecated {
+            reasons.push(Reason::new("deprecated", format!("Deprecated: {}", message)));
+        }
+        if !Self::has_recent_activity(last_publish_date, thresholds.max_days) {
+            let days = (Utc::now() - *last_publish_date).num_days();
+            reasons.push(Reason::new(
+                "no-recent-release",
+                format!("No release on '{}' for {} days (limit: {})", thresholds.tag, days, thresholds.max_days),
+            ));
+        }
+        if !Self::has_sufficient_downloads(weekly_downloads, monthly_downloads, thresholds) {
+            reasons.push(Reason::new(
+                "low-downloads",
+                format!(
+                    "{} weekly / {} monthly downloads (minimum: {} / {})",
+                    weekly_downloads, monthly_downloads, thresholds.min_weekly_downloads, thresholds.min_monthly_downloads
+                ),
+            ));
+        }
+        if total_versions < thresholds.min_versions {
+            reasons.push(Reason::new(
+                "few-versions",
+                format!("{} versions published (minimum: {})", total_versions, thresholds.min_versions),
+            ));
+        }
+        if maintainers_count < thresholds.min_maintainers {
+            reasons.push(Reason::new(
+                "few-maintainers",
+                format!("{} maintainers (minimum: {})", maintainers_count, thresholds.min_maintainers),
+            ));
+        }
+
+        reasons
     }
 
-}
\ No newline at end of file
+    /// 0-100, for ranking packages against each other. Each of the four criteria
+    /// is worth 25 points, scaled down by how far the package is from its threshold.
+    pub fn health_score(
+        last_publish_date: &DateTime<Utc>,
+        total_versions: usize,
+        maintainers_count: usize,
+        weekly_downloads: u64,
+        monthly_downloads: u64,
+        thresholds: &Thresholds,
+    ) -> u32 {
+        let days = (Utc::now() - *last_publish_date).num_days().max(1);
+        let activity = if days <= thresholds.max_days {
+            1.0
+        } else {
+            thresholds.max_days.max(0) as f64 / days as f64
+        };
+        let downloads = ratio(weekly_downloads, thresholds.min_weekly_downloads)
+            .max(ratio(monthly_downloads, thresholds.min_monthly_downloads));
+        let versions = ratio(total_versions as u64, thresholds.min_versions as u64);
+        let maintainers = ratio(maintainers_count as u64, thresholds.min_maintainers as u64);
+
+        ((activity + downloads + versions + maintainers) * 25.0).round() as u32
+    }
+}
+
+// How much of a minimum is met, capped at 1.0; a minimum of zero is always met
+fn ratio(value: u64, minimum: u64) -> f64 {
+    if minimum == 0 {
+        1.0
+    } else {
+        (value as f64 / minimum as f64).min(1.0)
+    }
+}
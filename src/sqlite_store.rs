@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, TransactionBehavior};
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::history::{HistoryData, HistoryEntry, HistoryStore, RetentionPolicy};
+
+// Each entry upgrades the schema by one version, tracked in PRAGMA user_version.
+// Never edit an existing entry, append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE runs (
+        id INTEGER PRIMARY KEY,
+        package TEXT NOT NULL,
+        recorded_at TEXT NOT NULL,
+        report TEXT NOT NULL,
+        UNIQUE (package, recorded_at)
+    );",
+];
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// All packages and all runs in a single SQLite database.
+pub struct SqliteHistoryStore {
+    conn: Connection,
+    verbose: bool,
+}
+
+impl SqliteHistoryStore {
+    pub fn open(path: &str, verbose: bool) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open history database: {}", path))?;
+
+        // Concurrent runs wait for each other instead of failing with SQLITE_BUSY
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        let mut store = Self { conn, verbose };
+        store.migrate()
+            .with_context(|| format!("Failed to migrate history database: {}", path))?;
+
+        if verbose {
+            eprintln!("[VERBOSE] Opened history database: {}", path);
+        }
+
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<()> {
+        let version: i64 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let version = version as usize;
+
+        if version > MIGRATIONS.len() {
+            anyhow::bail!(
+                "Database schema version {} is newer than supported version {}",
+                version,
+                MIGRATIONS.len()
+            );
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+            tx.commit()?;
+
+            if self.verbose {
+                eprintln!("[VERBOSE] Migrated history database to schema version {}", index + 1);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Imports a JSON history file. Entries already in the database are skipped.
+    pub fn import_json(&mut self, path: &str) -> Result<usize> {
+        let history = HistoryData::load(path, self.verbose)?
+            .with_context(|| format!("History file not found: {}", path))?;
+
+        let tx = self.conn.transaction()?;
+        let mut imported = 0;
+        for entry in &history.entries {
+            imported += insert_entry(&tx, &entry.report.name, entry)?;
+        }
+        tx.commit()?;
+
+        if self.verbose {
+            eprintln!("[VERBOSE] Imported {} of {} entries from: {}", imported, history.entries.len(), path);
+        }
+
+        Ok(imported)
+    }
+}
+
+impl HistoryStore for SqliteHistoryStore {
+    fn load(&mut self, package: &str) -> Result<HistoryData> {
+        let history = load_history(&self.conn, package)?;
+
+        if self.verbose {
+            eprintln!("[VERBOSE] Loaded {} history entries for: {}", history.entries.len(), package);
+        }
+
+        Ok(history)
+    }
+
+    fn record(&mut self, package: &str, entry: HistoryEntry, policy: &RetentionPolicy) -> Result<()> {
+        // IMMEDIATE takes the write lock up front so the read below can't go stale
+        let tx = self.conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        let mut history = load_history(&tx, package)?;
+        history.append(entry.report.clone(), entry.recorded_at);
+        history.apply_retention(policy, entry.recorded_at);
+
+        let retained: HashSet<String> = history.entries.iter()
+            .map(|e| format_timestamp(&e.recorded_at))
+            .collect();
+
+        insert_entry(&tx, package, &entry)?;
+        {
+            let mut stmt = tx.prepare("SELECT id, recorded_at FROM runs WHERE package = ?1")?;
+            let expired = stmt
+                .query_map(params![package], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|(_, recorded_at)| !retained.contains(recorded_at))
+                .map(|(id, _)| id)
+                .collect::<Vec<_>>();
+            for id in expired {
+                tx.execute("DELETE FROM runs WHERE id = ?1", params![id])?;
+            }
+        }
+        tx.commit().context("Failed to write history database")?;
+
+        if self.verbose {
+            eprintln!("[VERBOSE] Saved history for: {}", package);
+        }
+
+        Ok(())
+    }
+}
+
+fn load_history(conn: &Connection, package: &str) -> Result<HistoryData> {
+    let mut stmt = conn.prepare(
+        "SELECT recorded_at, report FROM runs WHERE package = ?1 ORDER BY recorded_at",
+    )?;
+
+    let entries = stmt
+        .query_map(params![package], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .map(|row| {
+            let (recorded_at, report) = row?;
+            Ok(HistoryEntry {
+                recorded_at: DateTime::parse_from_rfc3339(&recorded_at)
+                    .with_context(|| format!("Invalid timestamp in history database: {}", recorded_at))?
+                    .with_timezone(&Utc),
+                report: serde_json::from_str(&report)
+                    .with_context(|| format!("Failed to parse stored report for: {}", package))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(HistoryData { entries })
+}
+
+fn insert_entry(conn: &Connection, package: &str, entry: &HistoryEntry) -> Result<usize> {
+    let report = serde_json::to_string(&entry.report).context("Failed to serialize report")?;
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO runs (package, recorded_at, report) VALUES (?1, ?2, ?3)",
+        params![package, format_timestamp(&entry.recorded_at), report],
+    )?;
+    Ok(inserted)
+}
+
+// Fixed-width UTC timestamps sort lexicographically in chronological order
+fn format_timestamp(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
@@ -1,5 +1,9 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::check::CheckResult;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NpmPackageInfo {
@@ -17,6 +21,32 @@ pub struct NpmPackageInfo {
 }
 
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DistTag {
+    pub version: String,
+    pub published: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Maintainer {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+/// A liveness criterion the package does not meet. `rule` is a stable
+/// identifier, `message` explains it with the package's actual numbers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Reason {
+    pub rule: String,
+    pub message: String,
+}
+
+impl Reason {
+    pub fn new(rule: &str, message: String) -> Self {
+        Self { rule: rule.to_string(), message }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NpmDownloadStats {
     pub downloads: u64,
@@ -25,20 +55,91 @@ pub struct NpmDownloadStats {
     pub package: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// `default` lets history written before a field existed still deserialize
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct PackageReport {
     pub name: String,
+    /// Version the `latest` dist-tag points to.
     pub latest_version: String,
+    /// Number of versions ever published.
     pub total_versions: usize,
+    /// When `latest_version` was published.
     pub last_publish_date: DateTime<Utc>,
+    /// Missing in history recorded before dist-tags were tracked, which is no baseline to compare against.
+    pub dist_tags: Option<BTreeMap<String, DistTag>>,
     pub downloads_last_week: u64,
     pub downloads_last_month: u64,
     pub maintainers_count: usize,
+    pub maintainers: Vec<Maintainer>,
+    /// A release on the configured tag within `max_days`.
     pub has_recent_activity: bool,
+    /// The verdict of the liveness criteria.
     pub package_alive: bool,
+    /// 0-100, see the README for how it is computed.
+    pub health_score: u32,
+    /// The registry's deprecation message for the latest version.
+    pub deprecated: Option<String>,
+    /// The liveness criteria the package falls short of.
+    pub reasons: Vec<Reason>,
     pub description: Option<String>,
     pub homepage: Option<String>,
     pub repository_url: Option<String>,
     pub license: Option<String>,
     pub keywords: Option<Vec<String>>,
-}
\ No newline at end of file
+}
+
+/// Bumped whenever a field of the JSON output is renamed, removed or changes
+/// type. Added fields don't bump it, so consumers should ignore unknown fields.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// The thresholds the reports were scored with.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct Thresholds {
+    pub tag: String,
+    pub max_days: i64,
+    pub min_weekly_downloads: u64,
+    pub min_monthly_downloads: u64,
+    pub min_versions: usize,
+    pub min_maintainers: usize,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            tag: "latest".to_string(),
+            max_days: 90,
+            min_weekly_downloads: 1000,
+            min_monthly_downloads: 5000,
+            min_versions: 10,
+            min_maintainers: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Tool {
+    pub name: String,
+    pub version: String,
+}
+
+/// The document printed by `--format json` and `--format yaml`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReportEnvelope {
+    pub schema_version: u32,
+    pub tool: Tool,
+    pub generated_at: DateTime<Utc>,
+    pub thresholds: Thresholds,
+    /// Packages scored with other thresholds, from `[packages."..."]` config sections.
+    pub package_thresholds: BTreeMap<String, Thresholds>,
+    /// One full report per package; empty with `--fields`, see `projections`.
+    pub reports: Vec<PackageReport>,
+    /// The `--fields` paths, in order. Only present with `--fields`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<String>>,
+    /// With `--fields`, one object per package mapping each path in `fields` to its value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub projections: Option<Vec<serde_json::Map<String, serde_json::Value>>>,
+    /// `--check` results of every package, empty without `--check`.
+    pub checks: Vec<CheckResult>,
+}
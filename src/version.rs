@@ -0,0 +1,139 @@
+use schemars::JsonSchema;
+use semver::Version;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionChangeKind {
+    Major,
+    Minor,
+    Patch,
+    /// A pre-release became latest, or only the pre-release/build part changed.
+    Prerelease,
+    /// Latest moved to a lower version. Rollbacks like this are suspicious.
+    Downgrade,
+    /// At least one side is not valid semver.
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct VersionChange {
+    pub kind: VersionChangeKind,
+    /// Published versions strictly between the old and the new version.
+    pub versions_skipped: usize,
+}
+
+impl VersionChangeKind {
+    /// Rank for "at least" comparisons of upgrades, `None` for other kinds.
+    pub fn level(self) -> Option<u8> {
+        match self {
+            Self::Patch => Some(1),
+            Self::Minor => Some(2),
+            Self::Major => Some(3),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+            Self::Prerelease => "prerelease",
+            Self::Downgrade => "downgrade",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl VersionChange {
+    /// Classifies the move from `old` to `new`; `None` when they are equal.
+    /// `published` is the package's full version list, used to count skipped versions.
+    pub fn between(old: &str, new: &str, published: &[String]) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+
+        let (Ok(old), Ok(new)) = (parse(old), parse(new)) else {
+            return Some(Self { kind: VersionChangeKind::Unknown, versions_skipped: 0 });
+        };
+
+        let kind = if new < old {
+            VersionChangeKind::Downgrade
+        } else if !new.pre.is_empty() {
+            VersionChangeKind::Prerelease
+        } else if new.major != old.major {
+            VersionChangeKind::Major
+        } else if new.minor != old.minor {
+            VersionChangeKind::Minor
+        } else if new.patch != old.patch {
+            VersionChangeKind::Patch
+        } else {
+            VersionChangeKind::Prerelease
+        };
+
+        let (low, high) = if old < new { (&old, &new) } else { (&new, &old) };
+        let versions_skipped = published.iter()
+            .filter_map(|v| parse(v).ok())
+            .filter(|v| v > low && v < high)
+            .count();
+
+        Some(Self { kind, versions_skipped })
+    }
+}
+
+/// Report fields holding a version: `latest_version` and `dist_tags.<tag>.version`.
+pub fn is_version_field(field: &str) -> bool {
+    field == "latest_version" || (field.starts_with("dist_tags.") && field.ends_with(".version"))
+}
+
+fn parse(version: &str) -> Result<Version, semver::Error> {
+    Version::parse(version.trim().trim_start_matches('v'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(old: &str, new: &str) -> Option<VersionChangeKind> {
+        VersionChange::between(old, new, &[]).map(|change| change.kind)
+    }
+
+    #[test]
+    fn classifies_moves() {
+        assert_eq!(kind("1.2.3", "1.2.3"), None);
+        assert_eq!(kind("1.2.3", "2.0.0"), Some(VersionChangeKind::Major));
+        assert_eq!(kind("1.2.3", "1.3.0"), Some(VersionChangeKind::Minor));
+        assert_eq!(kind("1.2.3", "1.2.4"), Some(VersionChangeKind::Patch));
+        assert_eq!(kind("1.2.3", "2.0.0-rc.1"), Some(VersionChangeKind::Prerelease));
+        assert_eq!(kind("2.0.0-rc.1", "2.0.0"), Some(VersionChangeKind::Prerelease));
+        assert_eq!(kind("1.2.3", "1.2.2"), Some(VersionChangeKind::Downgrade));
+        assert_eq!(kind("v1.2.3", "1.3.0"), Some(VersionChangeKind::Minor));
+        assert_eq!(kind("1.2", "1.3"), Some(VersionChangeKind::Unknown));
+    }
+
+    #[test]
+    fn counts_skipped_versions() {
+        let published: Vec<String> = ["1.0.0", "1.1.0", "1.2.0-beta.1", "1.2.0", "2.0.0", "junk"]
+            .iter().map(|v| v.to_string()).collect();
+        let change = |old, new| VersionChange::between(old, new, &published).unwrap().versions_skipped;
+        assert_eq!(change("1.0.0", "2.0.0"), 3);
+        assert_eq!(change("1.0.0", "1.1.0"), 0);
+        assert_eq!(change("2.0.0", "1.0.0"), 3);
+    }
+
+    #[test]
+    fn ranks_upgrades() {
+        assert!(VersionChangeKind::Major.level() > VersionChangeKind::Minor.level());
+        assert!(VersionChangeKind::Minor.level() > VersionChangeKind::Patch.level());
+        assert_eq!(VersionChangeKind::Downgrade.level(), None);
+    }
+
+    #[test]
+    fn recognizes_version_fields() {
+        assert!(is_version_field("latest_version"));
+        assert!(is_version_field("dist_tags.next.version"));
+        assert!(!is_version_field("dist_tags.next.published"));
+        assert!(!is_version_field("total_versions"));
+    }
+}